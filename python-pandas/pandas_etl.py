@@ -5,6 +5,7 @@ import psutil
 import os
 import time
 import json
+import sys
 
 class PandasETL:
     def __init__(self, file_path):
@@ -235,6 +236,10 @@ def run_pandas_benchmark(file_path, output_dir):
         etl.metrics['total_time'] = total_time
         peak_memory = max([v for k, v in etl.metrics.items() if isinstance(v, (int, float)) and 'memory' in k])
         etl.metrics['peak_memory'] = peak_memory
+
+        # Rewrite the metrics so they include save_time and the totals
+        with open(f"{output_dir}/pandas_metrics.json", 'w') as f:
+            json.dump(etl.metrics, f, indent=2, default=str)
         
         # Final summary
         print("\n" + "=" * 50)
@@ -253,8 +258,8 @@ def run_pandas_benchmark(file_path, output_dir):
         return None
 
 if __name__ == "__main__":
-    # Check if data file exists
-    data_file = "../data/yellow_tripdata_2015-01.csv"
+    # Check if data file exists (optional path argument, used by `compare-engines`)
+    data_file = sys.argv[1] if len(sys.argv) > 1 else "../data/yellow_tripdata_2015-01.csv"
     if not os.path.exists(data_file):
         print(f"❌ Data file not found: {data_file}")
         print("Please ensure the NYC taxi dataset is in the data/ directory")
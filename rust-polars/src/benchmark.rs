@@ -0,0 +1,337 @@
+use crate::etl::{read_frame, write_frame, EtlError, OutputFormat, PolarsETL};
+use crate::status;
+use polars::prelude::*;
+use std::time::Instant;
+
+// Rows in the small frame of `measure_lazy_overhead`, and how often each variant runs on it
+const SMALL_ROWS: usize = 1_000;
+const SMALL_REPEATS: u32 = 100;
+
+// Reference workload behind `machine_score`: generate and sum this many floats, best of N runs
+const REFERENCE_ELEMENTS: usize = 10_000_000;
+const REFERENCE_RUNS: u32 = 5;
+
+// Each join strategy of `compare_join_strategies` runs this often; the best run is recorded
+const JOIN_RUNS: u32 = 5;
+
+/// Seconds a fixed single-threaded reference workload (build and sum a 10M-element array)
+/// takes on this machine, best of 5. Lower is faster; dividing a stage time by it gives a
+/// time in "reference units" that can be compared across hosts.
+pub fn machine_score() -> f64 {
+    let mut best = f64::INFINITY;
+    for _ in 0..REFERENCE_RUNS {
+        let start = Instant::now();
+        let data: Vec<f64> = (0..REFERENCE_ELEMENTS).map(|i| (i % 1000) as f64 * 0.5).collect();
+        std::hint::black_box(data.iter().sum::<f64>());
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    best
+}
+
+// ------- focused micro-benchmarks on top of a pipeline run -------
+impl PolarsETL {
+    /// Run the reference workload and record `machine_score`; call before the pipeline
+    /// so it measures an idle machine.
+    pub fn record_machine_score(&mut self) -> &mut Self {
+        let score = machine_score();
+        self.metrics.insert("machine_score".into(), score);
+        status!("🖥️  Machine score: {:.4}s for the reference workload", score);
+        self
+    }
+
+    /// `secs` divided by the recorded `machine_score`, if there is one.
+    pub fn normalized(&self, secs: f64) -> Option<f64> {
+        self.metrics.get("machine_score").filter(|s| **s > 0.0).map(|s| secs / s)
+    }
+
+    /// Write the aggregated results in `format` and read them straight back,
+    /// recording `<format>_write_time`, `<format>_readback_time` and `<format>_bytes`.
+    /// Call after `aggregate_data`; files go to a scratch dir that is removed afterwards.
+    pub fn bench_roundtrip(&mut self, format: OutputFormat) -> Result<&mut Self, EtlError> {
+        format.ensure_supported()?;
+        status!("Round-tripping results as {}...", format.name());
+        let dir = std::env::temp_dir().join(format!("polars-roundtrip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+
+        let paths: Vec<String> = self
+            .results
+            .iter()
+            .map(|(stem, _)| format!("{}/{}.{}", dir.display(), stem, format.extension()))
+            .collect();
+
+        let start = Instant::now();
+        for ((_, df), path) in self.results.iter_mut().zip(&paths) {
+            write_frame(df, path, format)?;
+        }
+        let write_time = start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        for path in &paths {
+            read_frame(path, format)?;
+        }
+        let readback_time = start.elapsed().as_secs_f64();
+
+        let bytes: u64 = paths
+            .iter()
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let name = format.name();
+        self.metrics.insert(format!("{}_write_time", name), write_time);
+        self.metrics.insert(format!("{}_readback_time", name), readback_time);
+        self.metrics.insert(format!("{}_bytes", name), bytes as f64);
+        status!(
+            "✅ {}: write {:.3}s, read back {:.3}s ({} bytes)",
+            name, write_time, readback_time, bytes
+        );
+        Ok(self)
+    }
+
+    /// Load the CSV (all columns) once with type inference and once with every column
+    /// forced to String, then cast the strings back to the inferred types — the
+    /// "read everything as object, convert later" pattern. Records `typed_load_time`,
+    /// `string_load_time`, `string_convert_time` and `string_vs_typed_ratio`.
+    /// Call after `load_data`; errors if the input wasn't a single CSV file.
+    pub fn compare_typed_vs_string(&mut self) -> PolarsResult<&mut Self> {
+        let Some(path) = self.input_path.clone() else {
+            polars_bail!(ComputeError: "typed vs string load needs a single CSV input");
+        };
+        status!("Comparing typed vs all-string CSV load...");
+
+        let start = Instant::now();
+        let typed = self.csv_reader(&path).finish()?.collect()?;
+        let typed_time = start.elapsed().as_secs_f64();
+
+        // Inferring from zero rows leaves every column as String
+        let start = Instant::now();
+        let strings = self
+            .csv_reader(&path)
+            .with_infer_schema_length(Some(0))
+            .finish()?
+            .collect()?;
+        let string_time = start.elapsed().as_secs_f64();
+
+        let casts: Vec<Expr> = typed
+            .schema()
+            .iter()
+            .map(|(name, dtype)| match dtype {
+                DataType::String => col(name.clone()),
+                _ => col(name.clone()).cast(dtype.clone()),
+            })
+            .collect();
+        let start = Instant::now();
+        let converted = strings.clone().lazy().select(casts).collect()?;
+        let convert_time = start.elapsed().as_secs_f64();
+        drop(converted);
+
+        let ratio = (string_time + convert_time) / typed_time.max(f64::EPSILON);
+
+        self.metrics.insert("typed_load_time".into(), typed_time);
+        self.metrics.insert("string_load_time".into(), string_time);
+        self.metrics.insert("string_convert_time".into(), convert_time);
+        self.metrics.insert("string_vs_typed_ratio".into(), ratio);
+        status!(
+            "✅ typed {:.2}s vs string {:.2}s + convert {:.2}s ({:.1}x)",
+            typed_time, string_time, convert_time, ratio
+        );
+        Ok(self)
+    }
+
+    /// Run the daily aggregation with `group_by` (unordered groups) and `group_by_stable`
+    /// (groups in first-appearance order) and check both give the same values once sorted
+    /// by date. Records `groupby_time`, `groupby_stable_time`, `groupby_stable_ratio` and
+    /// `groupby_values_match` (1 when only the order differed). Call after `clean_data`.
+    pub fn compare_groupby_stability(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else {
+            return Ok(self);
+        };
+        status!("Comparing group_by vs group_by_stable...");
+
+        let with_date = df.with_columns([col("tpep_pickup_datetime").dt().date().alias("date")]);
+        let aggs: Vec<Expr> = self.agg_specs.iter().map(|s| self.agg_expr(s)).collect();
+
+        let start = Instant::now();
+        let unstable = with_date.clone().group_by([col("date")]).agg(aggs.clone()).collect()?;
+        let unstable_time = start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        let stable = with_date.group_by_stable([col("date")]).agg(aggs).collect()?;
+        let stable_time = start.elapsed().as_secs_f64();
+
+        let sorted = |df: DataFrame| df.sort(["date"], Default::default());
+        let matches = sorted(unstable)?.equals_missing(&sorted(stable)?);
+        let ratio = stable_time / unstable_time.max(f64::EPSILON);
+
+        self.metrics.insert("groupby_time".into(), unstable_time);
+        self.metrics.insert("groupby_stable_time".into(), stable_time);
+        self.metrics.insert("groupby_stable_ratio".into(), ratio);
+        self.metrics.insert("groupby_values_match".into(), if matches { 1.0 } else { 0.0 });
+        status!(
+            "{} group_by {:.2}s vs group_by_stable {:.2}s ({:.2}x){}",
+            if matches { "✅" } else { "❌" },
+            unstable_time,
+            stable_time,
+            ratio,
+            if matches { "" } else { ", values differ" }
+        );
+        Ok(self)
+    }
+
+    /// Attach the pickup zone name from a TLC zone lookup CSV (`LocationID,Borough,Zone,...`)
+    /// by `PULocationID` three ways and time each, best of 5 over the key column collected
+    /// once: a hash left join, the same hash join on both sides pre-sorted by the key (sort
+    /// included), and a broadcast lookup, i.e. gathering from the ~265 zone names held as one
+    /// dense array indexed by ID. Records `join_<strategy>_time`, `join_collect_time`,
+    /// `join_matched_rows` and `join_results_match`. Needs trips with `PULocationID` (2016-07+
+    /// schema); 2015 files only have coordinates, so it is skipped for them.
+    pub fn compare_join_strategies(&mut self, lookup: &str) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else {
+            return Ok(self);
+        };
+        if df.clone().collect_schema()?.get("PULocationID").is_none() {
+            status!("⏭️  Join strategies skipped: the input has no PULocationID column");
+            return Ok(self);
+        }
+        status!("Comparing join strategies for the zone lookup...");
+
+        let zones = LazyCsvReader::new(lookup)
+            .with_has_header(true)
+            .finish()?
+            .select([
+                col("LocationID").cast(DataType::Int64),
+                col("Zone").cast(DataType::String).alias("pickup_zone"),
+            ])
+            .collect()?;
+        // The key column is read and cleaned once, so the runs below time only the joins
+        let start = Instant::now();
+        let trips = self.collect_plan(df.select([col("PULocationID").cast(DataType::Int64)]))?;
+        let collect_time = start.elapsed().as_secs_f64();
+
+        let matched = |lf: LazyFrame| -> PolarsResult<i64> {
+            let out = lf.select([col("pickup_zone").count().cast(DataType::Int64)]).collect()?;
+            Ok(out.get_columns()[0].i64()?.get(0).unwrap_or(0))
+        };
+        let join = |trips: LazyFrame, zones: LazyFrame| {
+            trips.join(zones, [col("PULocationID")], [col("LocationID")], JoinArgs::new(JoinType::Left))
+        };
+        let best_of = |run: &dyn Fn() -> PolarsResult<i64>| -> PolarsResult<(i64, f64)> {
+            let mut best = (0, f64::INFINITY);
+            for _ in 0..JOIN_RUNS {
+                let start = Instant::now();
+                let n = run()?;
+                best = (n, best.1.min(start.elapsed().as_secs_f64()));
+            }
+            Ok(best)
+        };
+
+        let (hash, hash_time) = best_of(&|| matched(join(trips.clone().lazy(), zones.clone().lazy())))?;
+
+        let (presorted, presorted_time) = best_of(&|| {
+            let trips = trips.sort(["PULocationID"], Default::default())?;
+            let zones = zones.sort(["LocationID"], Default::default())?;
+            matched(join(trips.lazy(), zones.lazy()))
+        })?;
+
+        // Broadcast: zone names as a dense array indexed by LocationID; unknown IDs → null
+        let (broadcast, broadcast_time) = best_of(&|| {
+            let ids = zones.column("LocationID")?.i64()?;
+            let names = zones.column("pickup_zone")?.str()?;
+            let max_id = ids.max().unwrap_or(0).max(0);
+            let mut dense: Vec<Option<&str>> = vec![None; max_id as usize + 1];
+            for (id, name) in ids.into_iter().zip(names) {
+                if let Some(id) = id.filter(|id| *id >= 0) {
+                    dense[id as usize] = name;
+                }
+            }
+            let dense = Series::new("pickup_zone".into(), dense);
+            let id = col("PULocationID");
+            let index = when(id.clone().gt_eq(lit(0)).and(id.clone().lt_eq(lit(max_id))))
+                .then(id)
+                .otherwise(lit(Null {}))
+                .cast(IDX_DTYPE);
+            matched(trips.clone().lazy().with_columns([lit(dense).gather(index).alias("pickup_zone")]))
+        })?;
+
+        let agree = hash == presorted && hash == broadcast;
+        self.metrics.insert("join_collect_time".into(), collect_time);
+        self.metrics.insert("join_hash_time".into(), hash_time);
+        self.metrics.insert("join_presorted_hash_time".into(), presorted_time);
+        self.metrics.insert("join_broadcast_time".into(), broadcast_time);
+        self.metrics.insert("join_matched_rows".into(), hash as f64);
+        self.metrics.insert("join_results_match".into(), if agree { 1.0 } else { 0.0 });
+        status!(
+            "{} {} trips matched (best of {}): hash {:.3}s, pre-sorted hash {:.3}s, broadcast {:.3}s{}",
+            if agree { "✅" } else { "❌" },
+            hash,
+            JOIN_RUNS,
+            hash_time,
+            presorted_time,
+            broadcast_time,
+            if agree { "" } else { ", match counts differ" }
+        );
+        Ok(self)
+    }
+
+    /// Run the hourly averages on an in-memory frame eagerly (`DataFrame::group_by`) and
+    /// through `.lazy()...collect()`, on the first `SMALL_ROWS` cleaned rows (averaged over
+    /// `SMALL_REPEATS` runs) and on the full cleaned frame. `lazy_overhead_ms` is the lazy
+    /// minus the eager time on the small frame, i.e. the fixed cost of planning and
+    /// optimizing; `lazy_overhead_full_ms` is the same difference at full size.
+    /// Call after `clean_data`.
+    pub fn measure_lazy_overhead(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else {
+            return Ok(self);
+        };
+        status!("Measuring lazy vs eager overhead...");
+
+        let full = df
+            .with_columns([col("tpep_pickup_datetime").dt().hour().alias("hour")])
+            .select([col("hour"), col("trip_distance"), col("total_amount")])
+            .collect()?;
+        let small = full.head(Some(SMALL_ROWS));
+
+        let eager = |frame: &DataFrame| -> PolarsResult<DataFrame> {
+            frame.group_by(["hour"])?.select(["trip_distance", "total_amount"]).mean()
+        };
+        let lazy = |frame: &DataFrame| -> PolarsResult<DataFrame> {
+            frame
+                .clone()
+                .lazy()
+                .group_by([col("hour")])
+                .agg([col("trip_distance").mean(), col("total_amount").mean()])
+                .collect()
+        };
+        // Average milliseconds per run
+        let time_ms = |f: &dyn Fn(&DataFrame) -> PolarsResult<DataFrame>, frame: &DataFrame, runs: u32| {
+            let start = Instant::now();
+            for _ in 0..runs {
+                f(frame)?;
+            }
+            Ok::<_, PolarsError>(start.elapsed().as_secs_f64() * 1000.0 / runs as f64)
+        };
+
+        let eager_small = time_ms(&eager, &small, SMALL_REPEATS)?;
+        let lazy_small = time_ms(&lazy, &small, SMALL_REPEATS)?;
+        let eager_full = time_ms(&eager, &full, 1)?;
+        let lazy_full = time_ms(&lazy, &full, 1)?;
+
+        self.metrics.insert("eager_agg_ms".into(), eager_small);
+        self.metrics.insert("lazy_agg_ms".into(), lazy_small);
+        self.metrics.insert("lazy_overhead_ms".into(), lazy_small - eager_small);
+        self.metrics.insert("eager_agg_full_ms".into(), eager_full);
+        self.metrics.insert("lazy_agg_full_ms".into(), lazy_full);
+        self.metrics.insert("lazy_overhead_full_ms".into(), lazy_full - eager_full);
+        status!(
+            "✅ {} rows: eager {:.3}ms vs lazy {:.3}ms; {} rows: eager {:.1}ms vs lazy {:.1}ms",
+            small.height(),
+            eager_small,
+            lazy_small,
+            full.height(),
+            eager_full,
+            lazy_full
+        );
+        Ok(self)
+    }
+}
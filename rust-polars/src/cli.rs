@@ -0,0 +1,649 @@
+use polars::prelude::DataType;
+use polars_etl_benchmark::compare::{Tolerance, ToleranceConfig};
+use polars_etl_benchmark::etl::{
+    is_glob, parse_dtype, AggSpec, EtlError, ExtraColumnPolicy, NegativeDurationPolicy, OptimizationFlags,
+    OutOfPeriodPolicy, OutputFormat, PeriodCheck, Stage, Units,
+};
+use polars_etl_benchmark::queries::NamedQuery;
+use std::collections::HashMap;
+
+// =========================
+// CLI argument parsing
+// =========================
+
+// Optional micro-benchmarks run after the main pipeline
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bench {
+    TypedVsString,
+    GroupbyStability,
+    LazyOverhead,
+}
+
+impl Bench {
+    // Stage whose output the benchmark works on
+    fn requires(self) -> Stage {
+        match self {
+            Bench::TypedVsString => Stage::Load,
+            Bench::GroupbyStability | Bench::LazyOverhead => Stage::Clean,
+        }
+    }
+}
+
+impl std::str::FromStr for Bench {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "typed-vs-string" => Ok(Bench::TypedVsString),
+            "groupby-stability" => Ok(Bench::GroupbyStability),
+            "lazy-overhead" => Ok(Bench::LazyOverhead),
+            other => Err(format!("unknown benchmark '{}'", other)),
+        }
+    }
+}
+
+// `generate` subcommand: write a synthetic dataset instead of running the pipeline
+pub struct GenerateArgs {
+    pub rows: usize,
+    pub seed: u64,
+    pub out: String, // format from the extension (csv|parquet|arrow)
+}
+
+// `concurrent-queries` subcommand: load + clean, then a timed concurrent query mix
+pub struct ConcurrentArgs {
+    pub input: Option<String>,
+    pub queries: Vec<NamedQuery>, // the mix; empty = every named query
+    pub concurrency: usize,
+    pub duration_secs: u64,
+}
+
+// `compare-engines` subcommand: the full pipeline on every installed engine, side by side
+pub struct CompareEnginesArgs {
+    pub input: Option<String>,
+    pub parity_band: Option<f64>, // speedups within 1 ± this read as parity in the narrative
+}
+
+#[derive(Default)]
+pub struct CliArgs {
+    pub input: Option<String>, // CSV file, CSV glob or Parquet directory
+    pub file_read_concurrency: Option<usize>,
+    pub byte_range: Option<(u64, u64)>, // (start, len): parse only that slice of a CSV
+    pub budget_secs: Option<f64>,       // pick the sample size so load..sort_filter takes this long
+    pub sample_size: Option<usize>,     // read only the first n rows
+    pub streaming_chunk_size: Option<usize>,
+    pub optimizations: OptimizationFlags,
+    pub reorder_columns: Option<Vec<String>>,
+    pub use_columns: Option<Vec<String>>, // the only input columns to read
+    pub extra_columns: ExtraColumnPolicy,
+    pub decimal_comma: bool,
+    pub separator: Option<u8>, // CSV field separator (default ',')
+    pub negative_durations: NegativeDurationPolicy,
+    pub period: PeriodCheck,
+    pub period_margin_days: Option<i64>,
+    pub out_of_period: OutOfPeriodPolicy,
+    pub coerce_types: HashMap<String, DataType>,
+    pub stages: Option<Vec<Stage>>, // None = every stage
+    pub agg_specs: Vec<AggSpec>, // empty = use the default daily measures
+    pub roundtrip: Vec<OutputFormat>,
+    pub queries: Vec<NamedQuery>,
+    pub benches: Vec<Bench>,
+    pub join_lookup: Option<String>, // zone lookup CSV for the join strategy comparison
+    pub label: Option<String>,
+    pub preview: Option<usize>,      // rows of each result to print
+    pub date_display: Option<String>, // strftime for dates in the preview (None = ISO)
+    pub summary_md: bool, // Markdown stage table on stdout, everything else on stderr
+    pub baseline: Option<String>,        // previous run's manifest or metrics JSON, or a directory of runs
+    pub baseline_label: Option<String>,  // only a baseline run carrying this label qualifies
+    pub fail_on_regression: Option<f64>, // exit 1 when a stage is this many percent slower
+    pub validate_only: bool, // smoke test on a 100-row sample instead of a benchmark
+    pub output_format: Option<OutputFormat>, // None = CSV
+    pub units: Units,
+    pub deterministic_sums: bool,
+    pub compare: Option<(String, String)>,   // parity check instead of a pipeline run
+    pub compare_key: Option<String>,         // None = first column of the first file
+    pub tolerances: ToleranceConfig,
+    pub hot_columns: Vec<String>,
+    pub trip_sets: bool,
+    pub audit: bool,
+    pub night_split: bool,
+    pub surge_indicator: bool,
+    pub quantiles: bool,
+    pub approximate_quantiles: bool,
+    pub quantile_sample_rows: Option<usize>,
+    pub explain_analyze: bool,
+    pub generate: Option<GenerateArgs>,
+    pub concurrent: Option<ConcurrentArgs>,
+    pub compare_engines: Option<CompareEnginesArgs>,
+    pub replay: Option<String>, // manifest of the run to reproduce
+    pub matrix: Option<String>, // matrix config to sweep
+}
+
+// Help text; `parse_args` returns exactly this as the error for `-h` / `--help`
+pub const USAGE: &str = "\
+Usage: polars-etl-benchmark [OPTIONS]
+       polars-etl-benchmark generate --rows <n> --out <file> [--seed <n>]
+       polars-etl-benchmark replay <benchmark_manifest.json>
+       polars-etl-benchmark concurrent-queries [--input <path>] [--query <name>]... [--concurrency <n>] [--duration <secs>]
+       polars-etl-benchmark compare-engines [--input <file>] [--parity-band <fraction>]
+       polars-etl-benchmark matrix --config <matrix.toml>
+
+Every pipeline run writes ../results/benchmark_manifest.json (arguments, thread caps, metrics);
+`replay` re-runs it and reports how the metrics drifted (exit 1 if a count/row metric changed).
+`matrix` runs the pipeline once per combination of the config's axes (threads × sample sizes × formats)
+and writes one metrics record per cell to a JSON Lines file (see matrix.example.toml).
+
+Options:
+  --input <path>               CSV file, CSV glob (e.g. '../data/yellow_tripdata_2015-*.csv') or
+                               directory of Parquet files (default ../data/yellow_tripdata_2015-01.csv)
+  --file-read-concurrency <n>  Files read in parallel by the multi-file loaders (default: all cores)
+  --byte-range <start>:<len>   Load only ~len bytes of the CSV from byte offset start (partial lines at the
+                               edges are dropped), e.g. a quick sample from the middle of a huge file
+  --sample-size <n>            Read only the first n rows of the input
+  --budget <secs>              Calibrate on two small samples, then pick the sample size so load through
+                               sort_filter takes about this long; reports target vs. actual
+  --extra-columns <policy>     Input columns outside the taxi schema: ignore (default), include or error
+  --decimal-comma              CSV numbers use decimal commas and thousands dots (1.234,56)
+  --separator <char>           CSV field separator (default ','; European exports usually use ';')
+  --negative-durations <policy>
+                               Trips with dropoff before pickup: drop (default), clamp_zero (duration 0)
+                               or keep; counted as negative_duration_rows either way
+  --period <period>            Dates the pickups should fall in: auto (default; the YYYY-MM in the file name),
+                               off, YYYY-MM or YYYY-MM-DD..YYYY-MM-DD (end exclusive); others are counted
+                               as out_of_period_rows
+  --period-margin-days <n>     Slack on both sides of the period (default 1)
+  --out-of-period <policy>     drop (default) or flag (keep, with an out_of_period column)
+  --coerce <column=type>       Cast a column right after load (repeatable; int32|int64|float32|float64|
+                               bool|string|date); values that fail become null, counted per column
+  --streaming-chunk-size <n>   Aggregate on the streaming engine, n rows per chunk (smaller = lower peak
+                               memory, slower; needs a build with `--features streaming`)
+  --reorder-columns <cols>     Put these columns (comma-separated) first after load, e.g. the ones the
+                               aggregation reads together; recorded as `column_order`
+  --use-columns <cols>         Read only these input columns (comma-separated), e.g. for a focused --agg run;
+                               cleaning rules on other columns are skipped (clean needs both timestamps)
+  --no-opt <name>              Turn a Polars optimization off for the pipeline's collects (repeatable;
+                               predicate_pushdown, projection_pushdown, slice_pushdown, comm_subexpr_elim,
+                               comm_subplan_elim; all on by default, recorded in the manifest)
+  --stages <list>              Run only these stages, in pipeline order (comma-separated:
+                               load,clean,aggregate,sort_filter,save; default all)
+  --agg <column:agg[:alias]>   Daily measure to compute (repeatable; agg = count|sum|mean|min|max|mode)
+  --query <name>               Run a named query after cleaning (repeatable; filter_stress, hourly_revenue,
+                               payment_split, top_pickup_cells)
+  --bench <name>               Extra micro-benchmark after the pipeline (repeatable;
+                               typed-vs-string (single CSV input only), groupby-stability, lazy-overhead)
+  --join-strategies <lookup>   Time hash, pre-sorted hash and broadcast joins of the trips' PULocationID against
+                               a zone lookup CSV (taxi_zone_lookup.csv); needs a schema with PULocationID
+  --validate-only              Smoke test: check the schema and run load/clean/aggregate/sort_filter on
+                               the first 100 rows, saving nothing; exits 1 on failure
+  --preview <rows>             Print the first rows of each aggregated result after the pipeline
+  --date-display <format>      strftime format for date/datetime columns in --preview, e.g. '%d %b %Y'
+                               (default ISO 8601; the saved results are unaffected)
+  --label <text>               Tag the run; stored as `label` in the metrics JSON
+  --summary md                 Print the stage timings as a Markdown table; it is the only output on
+                               stdout (progress goes to stderr), e.g. `... --summary md > summary.md`
+  --baseline <path>            Compare the stage timings with a previous run's benchmark_manifest.json or
+                               polars_metrics.json (read before this run overwrites ../results), or with the
+                               newest run in a directory of them (e.g. ../results/runs)
+  --baseline-label <text>      With --baseline: only a run labelled <text> (see --label) is used
+  --fail-on-regression <pct>   With --baseline: print GitHub Actions annotations for stages slower by more than
+                               half of pct (::warning::) or by more than pct (::error::); stages under 50 ms in
+                               both runs are ignored. Exit 1 on an error, a failed run or no shared timings
+  --output-format <format>     Format for the aggregated result files (csv|parquet|arrow, default csv)
+  --units <units>              Distances in the results: imperial (miles, default) or metric (km)
+  --deterministic-sums         Sum money columns exactly in cents so totals don't change with the thread count
+  --roundtrip <formats>        Time writing + reading back the results (comma-separated: csv,parquet,arrow)
+  --write-trip-sets            Also save the long/expensive trip rows as long_trips.parquet and
+                               expensive_trips.parquet next to the results
+  --audit                      Write ../results/audit.json: input rows, rows dropped by each cleaning
+                               rule and why, final rows and the retention percentage
+  --night-split                Also aggregate night (pickup 22:00-06:00) vs day trips: count, avg fare, avg tip
+  --surge-indicator            Append surge_indicator (fare/min vs. the pickup hour's median) after cleaning
+  --quantiles                  p50/p90/p99 of total_amount, trip_distance and trip duration after cleaning
+  --approximate-quantiles      Same, from a seeded random sample (implies --quantiles); records the
+                               method and a 95% rank-error bound
+  --quantile-sample-rows <n>   Sample size for --approximate-quantiles (default 100000; larger = more
+                               accurate, slower)
+  --explain-analyze            Profile the daily aggregation and print per-operator timings
+  --hot-columns <cols>         Keep these columns (comma-separated) materialized after cleaning
+  --compare <a> <b>            Compare two result files (csv|parquet|arrow) by key instead of running
+                               the pipeline; exits 1 when they differ
+  --key <column>               Key column for --compare (default: first column of <a>)
+  --tolerance <spec>           Float tolerance for --compare: exact, abs:<x>, rel:<x> or a bare absolute
+                               number (default rel 1e-9; integers always compare exactly)
+  --column-tolerance <col=spec>
+                               Tolerance for one column, overriding its dtype default (repeatable)
+  --ignore-case                Compare text columns case-insensitively in --compare
+  -h, --help                   Print this help
+
+Generate options:
+  --rows <n>                   Number of synthetic trips (default 100000)
+  --seed <n>                   RNG seed; the same seed gives the same file (default 42)
+  --out <file>                 Output file; csv, parquet or arrow by extension
+
+Concurrent-queries options (load + clean, then fire the query mix from parallel workers):
+  --input <path>               As above
+  --query <name>               Query in the mix (repeatable; default every named query)
+  --concurrency <n>            Parallel workers (default 8)
+  --duration <secs>            How long to keep the workers busy (default 10)
+
+Compare-engines options (full pipeline on Polars, then pandas and DuckDB when installed, side by side):
+  --input <file>               CSV file (default ../data/yellow_tripdata_2015-01.csv)
+  --parity-band <fraction>     Stage ratios within 1 ± this are called parity in the speedup summary (default 0.1)";
+
+// Reject formats missing from this build up front rather than after the pipeline ran
+fn parse_format(s: &str) -> Result<OutputFormat, String> {
+    let format: OutputFormat = s.parse()?;
+    if !format.is_supported() {
+        return Err(EtlError::FormatNotSupported { format: format.name() }.to_string());
+    }
+    Ok(format)
+}
+
+fn parse_generate<I: Iterator<Item = String>>(mut args: I) -> Result<GenerateArgs, String> {
+    let mut generate = GenerateArgs { rows: 100_000, seed: 42, out: String::new() };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rows" => {
+                let n = args.next().ok_or("--rows requires a value")?;
+                generate.rows = n.parse().map_err(|_| format!("invalid --rows '{}'", n))?;
+            }
+            "--seed" => {
+                let n = args.next().ok_or("--seed requires a value")?;
+                generate.seed = n.parse().map_err(|_| format!("invalid --seed '{}'", n))?;
+            }
+            "--out" => {
+                generate.out = args.next().ok_or("--out requires a value")?;
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown generate argument '{}'\n\n{}", other, USAGE)),
+        }
+    }
+
+    if generate.out.is_empty() {
+        return Err(format!("generate requires --out <file>\n\n{}", USAGE));
+    }
+    Ok(generate)
+}
+
+fn parse_concurrent<I: Iterator<Item = String>>(mut args: I) -> Result<ConcurrentArgs, String> {
+    let mut concurrent = ConcurrentArgs {
+        input: None,
+        queries: Vec::new(),
+        concurrency: 8,
+        duration_secs: 10,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                concurrent.input = Some(args.next().ok_or("--input requires a value")?);
+            }
+            "--query" => {
+                let name = args.next().ok_or("--query requires a value")?;
+                concurrent.queries.push(name.parse()?);
+            }
+            "--concurrency" => {
+                let n = args.next().ok_or("--concurrency requires a value")?;
+                concurrent.concurrency = n.parse().map_err(|_| format!("invalid --concurrency '{}'", n))?;
+            }
+            "--duration" => {
+                let n = args.next().ok_or("--duration requires a value")?;
+                concurrent.duration_secs = n.parse().map_err(|_| format!("invalid --duration '{}'", n))?;
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown concurrent-queries argument '{}'\n\n{}", other, USAGE)),
+        }
+    }
+
+    if concurrent.queries.is_empty() {
+        concurrent.queries = NamedQuery::ALL.to_vec();
+    }
+    Ok(concurrent)
+}
+
+fn parse_compare_engines<I: Iterator<Item = String>>(mut args: I) -> Result<CompareEnginesArgs, String> {
+    let mut compare = CompareEnginesArgs { input: None, parity_band: None };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                compare.input = Some(args.next().ok_or("--input requires a value")?);
+            }
+            "--parity-band" => {
+                let n = args.next().ok_or("--parity-band requires a value")?;
+                let band: f64 = n.parse().map_err(|_| format!("invalid --parity-band '{}'", n))?;
+                if !(0.0..1.0).contains(&band) {
+                    return Err(format!("--parity-band must be in [0, 1), got {}", n));
+                }
+                compare.parity_band = Some(band);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown compare-engines argument '{}'\n\n{}", other, USAGE)),
+        }
+    }
+    Ok(compare)
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    let mut args = args.peekable();
+    match args.peek().map(String::as_str) {
+        Some("generate") => {
+            args.next();
+            cli.generate = Some(parse_generate(args)?);
+            return Ok(cli);
+        }
+        Some("replay") => {
+            args.next();
+            let path = args.next().ok_or_else(|| format!("replay requires a manifest\n\n{}", USAGE))?;
+            if let Some(extra) = args.next() {
+                return Err(format!("unknown replay argument '{}'", extra));
+            }
+            cli.replay = Some(path);
+            return Ok(cli);
+        }
+        Some("concurrent-queries") => {
+            args.next();
+            cli.concurrent = Some(parse_concurrent(args)?);
+            return Ok(cli);
+        }
+        Some("matrix") => {
+            args.next();
+            let config = match (args.next().as_deref(), args.next()) {
+                (Some("--config"), Some(path)) => path,
+                _ => return Err(format!("matrix requires --config <matrix.toml>\n\n{}", USAGE)),
+            };
+            if let Some(extra) = args.next() {
+                return Err(format!("unknown matrix argument '{}'", extra));
+            }
+            cli.matrix = Some(config);
+            return Ok(cli);
+        }
+        Some("compare-engines") => {
+            args.next();
+            cli.compare_engines = Some(parse_compare_engines(args)?);
+            return Ok(cli);
+        }
+        _ => {}
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                cli.input = Some(args.next().ok_or("--input requires a value")?);
+            }
+            "--file-read-concurrency" => {
+                let n = args.next().ok_or("--file-read-concurrency requires a value")?;
+                let n: usize = n
+                    .parse()
+                    .map_err(|_| format!("invalid --file-read-concurrency '{}'", n))?;
+                cli.file_read_concurrency = Some(n);
+            }
+            "--extra-columns" => {
+                let policy = args.next().ok_or("--extra-columns requires a value")?;
+                cli.extra_columns = policy.parse()?;
+            }
+            "--decimal-comma" => {
+                cli.decimal_comma = true;
+            }
+            "--separator" => {
+                let sep = args.next().ok_or("--separator requires a value")?;
+                match sep.as_bytes() {
+                    [b] if b.is_ascii() && *b != b'"' && *b != b'\n' => cli.separator = Some(*b),
+                    _ => return Err(format!("invalid --separator '{}' (one ASCII character)", sep)),
+                }
+            }
+            "--negative-durations" => {
+                let policy = args.next().ok_or("--negative-durations requires a value")?;
+                cli.negative_durations = policy.parse()?;
+            }
+            "--period" => {
+                let period = args.next().ok_or("--period requires a value")?;
+                cli.period = period.parse()?;
+            }
+            "--period-margin-days" => {
+                let n = args.next().ok_or("--period-margin-days requires a value")?;
+                cli.period_margin_days =
+                    Some(n.parse().map_err(|_| format!("invalid --period-margin-days '{}'", n))?);
+            }
+            "--out-of-period" => {
+                let policy = args.next().ok_or("--out-of-period requires a value")?;
+                cli.out_of_period = policy.parse()?;
+            }
+            "--coerce" => {
+                let spec = args.next().ok_or("--coerce requires a value")?;
+                let (column, dtype) = spec
+                    .split_once('=')
+                    .ok_or_else(|| format!("invalid --coerce '{}' (expected column=type)", spec))?;
+                cli.coerce_types.insert(column.to_string(), parse_dtype(dtype)?);
+            }
+            "--streaming-chunk-size" => {
+                let n = args.next().ok_or("--streaming-chunk-size requires a value")?;
+                let n: usize = n
+                    .parse()
+                    .map_err(|_| format!("invalid --streaming-chunk-size '{}'", n))?;
+                if !cfg!(feature = "streaming") {
+                    return Err("--streaming-chunk-size needs a build with `--features streaming`".into());
+                }
+                cli.streaming_chunk_size = Some(n.max(1));
+            }
+            "--byte-range" => {
+                let range = args.next().ok_or("--byte-range requires a value")?;
+                let invalid = || format!("invalid --byte-range '{}' (expected start:len)", range);
+                let (start, len) = range.split_once(':').ok_or_else(invalid)?;
+                cli.byte_range = Some((
+                    start.trim().parse().map_err(|_| invalid())?,
+                    len.trim().parse().map_err(|_| invalid())?,
+                ));
+            }
+            "--sample-size" => {
+                let n = args.next().ok_or("--sample-size requires a value")?;
+                let n: usize = n.parse().map_err(|_| format!("invalid --sample-size '{}'", n))?;
+                if n == 0 {
+                    return Err("--sample-size must be at least 1".into());
+                }
+                cli.sample_size = Some(n);
+            }
+            "--budget" => {
+                let secs = args.next().ok_or("--budget requires a value")?;
+                let secs: f64 = secs.parse().map_err(|_| format!("invalid --budget '{}'", secs))?;
+                if !(secs > 0.0 && secs.is_finite()) {
+                    return Err("--budget must be a positive number of seconds".into());
+                }
+                cli.budget_secs = Some(secs);
+            }
+            "--use-columns" => {
+                let cols = args.next().ok_or("--use-columns requires a value")?;
+                cli.use_columns = Some(cols.split(',').map(|c| c.trim().to_string()).collect());
+            }
+            "--reorder-columns" => {
+                let cols = args.next().ok_or("--reorder-columns requires a value")?;
+                cli.reorder_columns = Some(cols.split(',').map(|c| c.trim().to_string()).collect());
+            }
+            "--no-opt" => {
+                let name = args.next().ok_or("--no-opt requires a value")?;
+                cli.optimizations.disable(&name)?;
+            }
+            "--stages" => {
+                let list = args.next().ok_or("--stages requires a value")?;
+                cli.stages = Some(Stage::parse_list(&list)?);
+            }
+            "--agg" => {
+                let spec = args.next().ok_or("--agg requires a value")?;
+                cli.agg_specs.push(AggSpec::parse(&spec)?);
+            }
+            "--query" => {
+                let name = args.next().ok_or("--query requires a value")?;
+                cli.queries.push(name.parse()?);
+            }
+            "--bench" => {
+                let name = args.next().ok_or("--bench requires a value")?;
+                cli.benches.push(name.parse()?);
+            }
+            "--join-strategies" => {
+                cli.join_lookup = Some(args.next().ok_or("--join-strategies requires a value")?);
+            }
+            "--summary" => {
+                let format = args.next().ok_or("--summary requires a value")?;
+                match format.as_str() {
+                    "md" | "markdown" => cli.summary_md = true,
+                    other => return Err(format!("unknown summary format '{}' (md)", other)),
+                }
+            }
+            "--validate-only" => {
+                cli.validate_only = true;
+            }
+            "--preview" => {
+                let n = args.next().ok_or("--preview requires a value")?;
+                cli.preview = Some(n.parse().map_err(|_| format!("invalid --preview '{}'", n))?);
+            }
+            "--date-display" => {
+                cli.date_display = Some(args.next().ok_or("--date-display requires a value")?);
+            }
+            "--label" => {
+                cli.label = Some(args.next().ok_or("--label requires a value")?);
+            }
+            "--output-format" => {
+                let format = args.next().ok_or("--output-format requires a value")?;
+                cli.output_format = Some(parse_format(&format)?);
+            }
+            "--units" => {
+                let units = args.next().ok_or("--units requires a value")?;
+                cli.units = units.parse()?;
+            }
+            "--deterministic-sums" => {
+                cli.deterministic_sums = true;
+            }
+            "--roundtrip" => {
+                let formats = args.next().ok_or("--roundtrip requires a value")?;
+                for f in formats.split(',') {
+                    cli.roundtrip.push(parse_format(f.trim())?);
+                }
+            }
+            "--write-trip-sets" => {
+                cli.trip_sets = true;
+            }
+            "--audit" => {
+                cli.audit = true;
+            }
+            "--night-split" => {
+                cli.night_split = true;
+            }
+            "--surge-indicator" => {
+                cli.surge_indicator = true;
+            }
+            "--quantiles" => {
+                cli.quantiles = true;
+            }
+            "--approximate-quantiles" => {
+                cli.quantiles = true;
+                cli.approximate_quantiles = true;
+            }
+            "--quantile-sample-rows" => {
+                let n = args.next().ok_or("--quantile-sample-rows requires a value")?;
+                cli.quantile_sample_rows =
+                    Some(n.parse().map_err(|_| format!("invalid --quantile-sample-rows '{}'", n))?);
+            }
+            "--explain-analyze" => {
+                cli.explain_analyze = true;
+            }
+            "--hot-columns" => {
+                let cols = args.next().ok_or("--hot-columns requires a value")?;
+                cli.hot_columns.extend(cols.split(',').map(|c| c.trim().to_string()));
+            }
+            "--compare" => {
+                let a = args.next().ok_or("--compare requires two files")?;
+                let b = args.next().ok_or("--compare requires two files")?;
+                cli.compare = Some((a, b));
+            }
+            "--key" => {
+                cli.compare_key = Some(args.next().ok_or("--key requires a value")?);
+            }
+            "--tolerance" => {
+                let spec = args.next().ok_or("--tolerance requires a value")?;
+                cli.tolerances.floats = spec.parse()?;
+            }
+            "--column-tolerance" => {
+                let spec = args.next().ok_or("--column-tolerance requires a value")?;
+                let (column, tolerance) = spec
+                    .split_once('=')
+                    .ok_or_else(|| format!("invalid --column-tolerance '{}' (expected col=spec)", spec))?;
+                cli.tolerances.columns.insert(column.to_string(), tolerance.parse()?);
+            }
+            "--ignore-case" => {
+                cli.tolerances.strings = Tolerance::CaseInsensitive;
+            }
+            "--baseline" => {
+                cli.baseline = Some(args.next().ok_or("--baseline requires a value")?);
+            }
+            "--baseline-label" => {
+                cli.baseline_label = Some(args.next().ok_or("--baseline-label requires a value")?);
+            }
+            "--fail-on-regression" => {
+                let pct = args.next().ok_or("--fail-on-regression requires a value")?;
+                let pct: f64 = pct
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_err(|_| format!("invalid --fail-on-regression '{}'", pct))?;
+                if !pct.is_finite() || pct < 0.0 {
+                    return Err("--fail-on-regression must be a non-negative percentage".into());
+                }
+                cli.fail_on_regression = Some(pct);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown argument '{}'\n\n{}", other, USAGE)),
+        }
+    }
+
+    let samplers = [cli.budget_secs.is_some(), cli.byte_range.is_some(), cli.sample_size.is_some()];
+    if samplers.iter().filter(|used| **used).count() > 1 {
+        return Err("--budget, --byte-range and --sample-size each pick the sample; use one".into());
+    }
+    if cli.date_display.is_some() && cli.preview.is_none() {
+        return Err("--date-display only applies to --preview".into());
+    }
+    if cli.use_columns.is_some() && !cli.optimizations.projection_pushdown {
+        return Err("--use-columns is read through projection pushdown; drop --no-opt projection_pushdown".into());
+    }
+    if cli.benches.contains(&Bench::TypedVsString) {
+        let input = cli.input.as_deref().unwrap_or_default();
+        if is_glob(input) || input.ends_with(".parquet") || std::path::Path::new(input).is_dir() {
+            return Err("--bench typed-vs-string re-reads a single CSV file; it can't use a glob or Parquet input".into());
+        }
+    }
+    if cli.fail_on_regression.is_some() && cli.baseline.is_none() {
+        return Err("--fail-on-regression needs a --baseline to compare with".into());
+    }
+    if cli.baseline_label.is_some() && cli.baseline.is_none() {
+        return Err("--baseline-label picks among --baseline runs; add --baseline".into());
+    }
+
+    // Extra steps hang off a stage; refuse them when that stage is skipped
+    if let Some(stages) = &cli.stages {
+        let needs = |used: bool, option: &str, stage: Stage| {
+            if used && !stages.contains(&stage) {
+                Err(format!("{} needs the '{}' stage", option, stage.name()))
+            } else {
+                Ok(())
+            }
+        };
+        needs(!cli.queries.is_empty(), "--query", Stage::Clean)?;
+        needs(!cli.hot_columns.is_empty(), "--hot-columns", Stage::Clean)?;
+        needs(!cli.roundtrip.is_empty(), "--roundtrip", Stage::Aggregate)?;
+        needs(cli.trip_sets, "--write-trip-sets", Stage::Save)?;
+        needs(cli.byte_range.is_some(), "--byte-range", Stage::Load)?;
+        needs(cli.budget_secs.is_some(), "--budget", Stage::Load)?;
+        needs(cli.sample_size.is_some(), "--sample-size", Stage::Load)?;
+        needs(cli.audit, "--audit", Stage::Clean)?;
+        needs(cli.preview.is_some(), "--preview", Stage::Aggregate)?;
+        needs(cli.night_split, "--night-split", Stage::Aggregate)?;
+        needs(cli.explain_analyze, "--explain-analyze", Stage::Clean)?;
+        needs(cli.surge_indicator, "--surge-indicator", Stage::Clean)?;
+        needs(cli.quantiles, "--quantiles", Stage::Clean)?;
+        needs(cli.join_lookup.is_some(), "--join-strategies", Stage::Clean)?;
+        for bench in &cli.benches {
+            needs(true, "--bench", bench.requires())?;
+        }
+    }
+
+    Ok(cli)
+}
@@ -0,0 +1,356 @@
+use polars::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+// ------- semantic result comparison (row/column order and float formatting don't matter) -------
+
+/// How close two values must be to count as equal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance {
+    Exact,
+    /// `|a - b| <= atol + rtol * |b|` (numeric columns).
+    Approx { atol: f64, rtol: f64 },
+    /// Text compared after lowercasing; exact for numbers.
+    CaseInsensitive,
+}
+
+impl Tolerance {
+    fn allows(self, a: f64, b: f64) -> bool {
+        match self {
+            Tolerance::Approx { atol, rtol } => (a - b).abs() <= atol + rtol * b.abs(),
+            Tolerance::Exact | Tolerance::CaseInsensitive => a == b,
+        }
+    }
+}
+
+impl std::fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tolerance::Exact => write!(f, "exact"),
+            Tolerance::Approx { atol, rtol } => write!(f, "atol {:e} rtol {:e}", atol, rtol),
+            Tolerance::CaseInsensitive => write!(f, "ignore case"),
+        }
+    }
+}
+
+impl std::str::FromStr for Tolerance {
+    type Err = String;
+
+    /// `exact`, `ignore-case`, `abs:<x>`, `rel:<x>` or a bare number (absolute).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |x: &str| x.parse::<f64>().map_err(|_| format!("invalid tolerance '{}'", s));
+        match s.split_once(':') {
+            None if s == "exact" => Ok(Tolerance::Exact),
+            None if s == "ignore-case" => Ok(Tolerance::CaseInsensitive),
+            None => Ok(Tolerance::Approx { atol: number(s)?, rtol: 0.0 }),
+            Some(("abs", x)) => Ok(Tolerance::Approx { atol: number(x)?, rtol: 0.0 }),
+            Some(("rel", x)) => Ok(Tolerance::Approx { atol: 0.0, rtol: number(x)? }),
+            Some(_) => Err(format!("invalid tolerance '{}' (exact|ignore-case|abs:<x>|rel:<x>)", s)),
+        }
+    }
+}
+
+/// Tolerances by dtype, with per-column overrides. Defaults: integers exact, floats
+/// within a relative 1e-9 (summation-order noise), strings exact.
+#[derive(Clone, Debug)]
+pub struct ToleranceConfig {
+    pub integers: Tolerance,
+    pub floats: Tolerance,
+    pub strings: Tolerance,
+    pub columns: HashMap<String, Tolerance>,
+}
+
+impl Default for ToleranceConfig {
+    fn default() -> Self {
+        ToleranceConfig {
+            integers: Tolerance::Exact,
+            floats: Tolerance::Approx { atol: 1e-12, rtol: 1e-9 },
+            strings: Tolerance::Exact,
+            columns: HashMap::new(),
+        }
+    }
+}
+
+impl ToleranceConfig {
+    pub fn with_floats(mut self, tolerance: Tolerance) -> Self {
+        self.floats = tolerance;
+        self
+    }
+
+    pub fn with_strings(mut self, tolerance: Tolerance) -> Self {
+        self.strings = tolerance;
+        self
+    }
+
+    pub fn with_column(mut self, column: impl Into<String>, tolerance: Tolerance) -> Self {
+        self.columns.insert(column.into(), tolerance);
+        self
+    }
+
+    // Column override first; mixed int/float columns compare as floats
+    fn for_column(&self, name: &str, a: &DataType, b: &DataType) -> Tolerance {
+        if let Some(t) = self.columns.get(name) {
+            *t
+        } else if a.is_float() || b.is_float() {
+            self.floats
+        } else if a.is_integer() && b.is_integer() {
+            self.integers
+        } else {
+            self.strings
+        }
+    }
+}
+
+/// Per-column outcome of [`compare_dataframes`].
+#[derive(Clone, Debug)]
+pub struct ColumnDiff {
+    pub column: String,
+    pub tolerance: Tolerance,
+    /// Largest absolute difference over the shared keys (numeric columns only;
+    /// infinite when only one side is null or NaN).
+    pub max_abs_error: f64,
+    /// Shared keys whose values are not equal under `tolerance`.
+    pub mismatches: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiffReport {
+    pub key: String,
+    pub rows_compared: usize,
+    pub added_keys: Vec<String>,   // present in `b` only
+    pub removed_keys: Vec<String>, // present in `a` only
+    pub columns_only_in_a: Vec<String>,
+    pub columns_only_in_b: Vec<String>,
+    pub columns: Vec<ColumnDiff>,
+}
+
+impl DiffReport {
+    /// Same keys, same columns and every value within tolerance.
+    pub fn is_match(&self) -> bool {
+        self.added_keys.is_empty()
+            && self.removed_keys.is_empty()
+            && self.columns_only_in_a.is_empty()
+            && self.columns_only_in_b.is_empty()
+            && self.columns.iter().all(|c| c.mismatches == 0)
+    }
+}
+
+impl std::fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Compared {} rows by '{}'",
+            self.rows_compared, self.key
+        )?;
+        let list = |keys: &[String]| keys.join(", ");
+        if !self.removed_keys.is_empty() {
+            writeln!(f, "  keys only in A: {}", list(&self.removed_keys))?;
+        }
+        if !self.added_keys.is_empty() {
+            writeln!(f, "  keys only in B: {}", list(&self.added_keys))?;
+        }
+        if !self.columns_only_in_a.is_empty() {
+            writeln!(f, "  columns only in A: {}", list(&self.columns_only_in_a))?;
+        }
+        if !self.columns_only_in_b.is_empty() {
+            writeln!(f, "  columns only in B: {}", list(&self.columns_only_in_b))?;
+        }
+        for c in &self.columns {
+            let mark = if c.mismatches == 0 { "✅" } else { "❌" };
+            writeln!(
+                f,
+                "  {} {:<28} max error {:<12.6e} mismatches {:<6} ({})",
+                mark, c.column, c.max_abs_error, c.mismatches, c.tolerance
+            )?;
+        }
+        write!(f, "{}", if self.is_match() { "MATCH" } else { "DIFFERENT" })
+    }
+}
+
+// Key values rendered as text so e.g. an i64 hour matches an i32 one
+fn key_index(df: &DataFrame, key: &str) -> PolarsResult<HashMap<String, usize>> {
+    let keys = df.column(key)?.cast(&DataType::String)?;
+    let keys = keys.str()?;
+    let mut index = HashMap::with_capacity(keys.len());
+    for (i, k) in keys.into_iter().enumerate() {
+        let k = k.unwrap_or("null").to_string();
+        if index.insert(k.clone(), i).is_some() {
+            polars_bail!(ComputeError: "duplicate key '{}' in column '{}'", k, key);
+        }
+    }
+    Ok(index)
+}
+
+// `rows` holds the (a row, b row) pairs of the keys both frames share
+fn compare_column(
+    a: &Column,
+    b: &Column,
+    rows: &[(usize, usize)],
+    tolerance: Tolerance,
+) -> PolarsResult<(f64, usize)> {
+    let mut max_abs_error = 0.0_f64;
+    let mut mismatches = 0;
+    let mut record = |err: f64, equal: bool| {
+        max_abs_error = max_abs_error.max(err);
+        if !equal {
+            mismatches += 1;
+        }
+    };
+
+    let (ta, tb) = (a.dtype(), b.dtype());
+    if ta.is_integer() && tb.is_integer() {
+        // i64 rather than f64 so large counts don't lose precision
+        let (a, b) = (a.cast(&DataType::Int64)?, b.cast(&DataType::Int64)?);
+        let (a, b) = (a.i64()?, b.i64()?);
+        for &(i, j) in rows {
+            match (a.get(i), b.get(j)) {
+                (Some(x), Some(y)) => {
+                    record(x.abs_diff(y) as f64, x == y || tolerance.allows(x as f64, y as f64))
+                }
+                (None, None) => record(0.0, true),
+                _ => record(f64::INFINITY, false),
+            }
+        }
+    } else if ta.is_primitive_numeric() && tb.is_primitive_numeric() {
+        let (a, b) = (a.cast(&DataType::Float64)?, b.cast(&DataType::Float64)?);
+        let (a, b) = (a.f64()?, b.f64()?);
+        for &(i, j) in rows {
+            match (a.get(i), b.get(j)) {
+                (Some(x), Some(y)) if x.is_nan() && y.is_nan() => record(0.0, true),
+                (Some(x), Some(y)) if !x.is_nan() && !y.is_nan() => {
+                    record((x - y).abs(), tolerance.allows(x, y))
+                }
+                (None, None) => record(0.0, true),
+                _ => record(f64::INFINITY, false),
+            }
+        }
+    } else {
+        let (a, b) = (a.cast(&DataType::String)?, b.cast(&DataType::String)?);
+        let (a, b) = (a.str()?, b.str()?);
+        for &(i, j) in rows {
+            let equal = match (a.get(i), b.get(j), tolerance) {
+                (Some(x), Some(y), Tolerance::CaseInsensitive) => x.to_lowercase() == y.to_lowercase(),
+                (x, y, _) => x == y,
+            };
+            record(0.0, equal);
+        }
+    }
+    Ok((max_abs_error, mismatches))
+}
+
+/// Compare two result frames aligned on `key`, independent of row and column order.
+/// Each shared column is checked with the tolerance `tolerances` picks for its dtype (or
+/// name); non-numeric columns are compared as text. Fails if `key` is missing or not
+/// unique on either side.
+pub fn compare_dataframes(
+    a: &DataFrame,
+    b: &DataFrame,
+    key: &str,
+    tolerances: &ToleranceConfig,
+) -> PolarsResult<DiffReport> {
+    let (index_a, index_b) = (key_index(a, key)?, key_index(b, key)?);
+
+    let keys_a: BTreeSet<&String> = index_a.keys().collect();
+    let keys_b: BTreeSet<&String> = index_b.keys().collect();
+    let removed_keys = keys_a.difference(&keys_b).map(|k| k.to_string()).collect();
+    let added_keys = keys_b.difference(&keys_a).map(|k| k.to_string()).collect();
+    let rows: Vec<(usize, usize)> = keys_a
+        .intersection(&keys_b)
+        .map(|k| (index_a[*k], index_b[*k]))
+        .collect();
+
+    let names = |df: &DataFrame| -> BTreeSet<String> {
+        df.get_column_names()
+            .into_iter()
+            .filter(|n| n.as_str() != key)
+            .map(|n| n.to_string())
+            .collect()
+    };
+    let (cols_a, cols_b) = (names(a), names(b));
+
+    let mut columns = Vec::new();
+    for name in cols_a.intersection(&cols_b) {
+        let (ca, cb) = (a.column(name)?, b.column(name)?);
+        let tolerance = tolerances.for_column(name, ca.dtype(), cb.dtype());
+        let (max_abs_error, mismatches) = compare_column(ca, cb, &rows, tolerance)?;
+        columns.push(ColumnDiff { column: name.clone(), tolerance, max_abs_error, mismatches });
+    }
+
+    Ok(DiffReport {
+        key: key.to_string(),
+        rows_compared: rows.len(),
+        added_keys,
+        removed_keys,
+        columns_only_in_a: cols_a.difference(&cols_b).cloned().collect(),
+        columns_only_in_b: cols_b.difference(&cols_a).cloned().collect(),
+        columns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mismatch count of `column` when comparing a and b on "k"
+    fn mismatches(a: &DataFrame, b: &DataFrame, tolerances: &ToleranceConfig, column: &str) -> usize {
+        let report = compare_dataframes(a, b, "k", tolerances).unwrap();
+        report.columns.iter().find(|c| c.column == column).unwrap().mismatches
+    }
+
+    #[test]
+    fn integers_compare_exactly() {
+        let a = df!("k" => [1, 2, 3], "n" => [10i64, 20, 30]).unwrap();
+        let same = df!("k" => [3, 1, 2], "n" => [30i32, 10, 20]).unwrap();
+        let off_by_one = df!("k" => [1, 2, 3], "n" => [10i64, 21, 30]).unwrap();
+
+        let report = compare_dataframes(&a, &same, "k", &ToleranceConfig::default()).unwrap();
+        assert!(report.is_match(), "{}", report);
+        let report = compare_dataframes(&a, &off_by_one, "k", &ToleranceConfig::default()).unwrap();
+        assert!(!report.is_match());
+        assert_eq!(report.columns[0].mismatches, 1);
+        assert_eq!(report.columns[0].max_abs_error, 1.0);
+    }
+
+    #[test]
+    fn float_tolerance_holds_at_the_bound_and_fails_past_it() {
+        let a = df!("k" => [1, 2], "x" => [10.0, 5.0]).unwrap();
+        let at_bound = df!("k" => [1, 2], "x" => [10.5, 4.0]).unwrap();
+        let past_bound = df!("k" => [1, 2], "x" => [10.75, 3.5]).unwrap();
+
+        // |a - b| <= atol: 0.5 passes, 0.75 doesn't
+        let atol = ToleranceConfig::default().with_floats(Tolerance::Approx { atol: 0.5, rtol: 0.0 });
+        let a1 = a.slice(0, 1);
+        assert_eq!(mismatches(&a1, &at_bound.slice(0, 1), &atol, "x"), 0);
+        assert_eq!(mismatches(&a1, &past_bound.slice(0, 1), &atol, "x"), 1);
+
+        // |a - b| <= rtol * |b|: 5 vs 4 is exactly 25% of b, 5 vs 3.5 is more
+        let rtol = ToleranceConfig::default().with_floats(Tolerance::Approx { atol: 0.0, rtol: 0.25 });
+        let a2 = a.slice(1, 1);
+        assert_eq!(mismatches(&a2, &at_bound.slice(1, 1), &rtol, "x"), 0);
+        assert_eq!(mismatches(&a2, &past_bound.slice(1, 1), &rtol, "x"), 1);
+    }
+
+    #[test]
+    fn strings_can_ignore_case() {
+        let a = df!("k" => [1, 2], "s" => ["Cash", "CARD"]).unwrap();
+        let b = df!("k" => [1, 2], "s" => ["cash", "card"]).unwrap();
+
+        assert_eq!(mismatches(&a, &b, &ToleranceConfig::default(), "s"), 2);
+        let ignore_case = ToleranceConfig::default().with_strings(Tolerance::CaseInsensitive);
+        assert_eq!(mismatches(&a, &b, &ignore_case, "s"), 0);
+    }
+
+    #[test]
+    fn column_override_beats_the_dtype_default() {
+        let a = df!("k" => [1], "fare" => [10.0], "tip" => [2.0]).unwrap();
+        let b = df!("k" => [1], "fare" => [10.5], "tip" => [2.5]).unwrap();
+        let tolerances = ToleranceConfig::default().with_column("fare", "abs:1".parse().unwrap());
+
+        let report = compare_dataframes(&a, &b, "k", &tolerances).unwrap();
+        let fare = report.columns.iter().find(|c| c.column == "fare").unwrap();
+        let tip = report.columns.iter().find(|c| c.column == "tip").unwrap();
+        assert_eq!(fare.tolerance, Tolerance::Approx { atol: 1.0, rtol: 0.0 });
+        assert_eq!(fare.mismatches, 0);
+        assert_eq!(tip.tolerance, ToleranceConfig::default().floats);
+        assert_eq!(tip.mismatches, 1);
+    }
+}
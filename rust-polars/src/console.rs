@@ -0,0 +1,26 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// ------- console chatter -------
+// Progress messages go to stdout by default. When stdout carries something meant to be
+// piped (e.g. the Markdown summary) they are moved to stderr instead.
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_status_to_stderr(enabled: bool) {
+    TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn status_to_stderr() -> bool {
+    TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// `println!` for progress chatter; honours [`set_status_to_stderr`].
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::console::status_to_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+// ------- side-by-side engine comparison (`compare-engines`) -------
+
+/// Stages timed on every engine: (metric key every runner records, table label, whether the
+/// stage does the same work everywhere). Polars' load and clean only build a lazy plan, so
+/// those two get no speedup or winner of their own.
+pub const STAGES: [(&str, &str, bool); 5] = [
+    ("load_time", "Load", false),
+    ("clean_time", "Clean", false),
+    ("aggregate_time", "Aggregate", true),
+    ("sort_filter_time", "Sort & filter", true),
+    ("save_time", "Save", true),
+];
+
+/// Stage timings (seconds) of one engine's run.
+#[derive(Clone, Debug)]
+pub struct EngineRun {
+    pub engine: &'static str,
+    pub stages: HashMap<String, f64>,
+}
+
+impl EngineRun {
+    /// Keep the compared stage timings out of a metrics map.
+    pub fn from_metrics(engine: &'static str, metrics: &HashMap<String, f64>) -> Self {
+        let stages = STAGES
+            .iter()
+            .filter_map(|(key, _, _)| metrics.get(*key).map(|v| (key.to_string(), *v)))
+            .collect();
+        EngineRun { engine, stages }
+    }
+
+    /// Sum of the stage times, so interpreter start-up and imports don't count.
+    pub fn total(&self) -> f64 {
+        self.stages.values().sum()
+    }
+
+    /// Seconds spent in the stages `keys`; None unless the run recorded every one of them.
+    pub fn group_time(&self, keys: &[&str]) -> Option<f64> {
+        keys.iter().map(|key| self.stages.get(*key).copied()).sum()
+    }
+}
+
+/// Run the pandas script on `input` with `python` (`PYTHON_BIN` overrides, as in
+/// `run_benchmarks.sh`) and read back its metrics. Err is the reason to skip the engine.
+pub fn run_pandas(input: &str, script_dir: &str, results_dir: &str) -> Result<EngineRun, String> {
+    let python = std::env::var("PYTHON_BIN").unwrap_or_else(|_| "python".into());
+    let probe = Command::new(&python).args(["-c", "import pandas, psutil"]).output();
+    match probe {
+        Ok(out) if out.status.success() => {}
+        Ok(_) => return Err(format!("pandas/psutil not installed for `{}`", python)),
+        Err(_) => return Err(format!("`{}` not found", python)),
+    }
+
+    // The script runs from its own directory, so hand it an absolute input path
+    let input = std::fs::canonicalize(input).map_err(|e| format!("{}: {}", input, e))?;
+    let status = Command::new(&python)
+        .arg("-u")
+        .arg("pandas_etl.py")
+        .arg(&input)
+        .current_dir(script_dir)
+        .status()
+        .map_err(|e| format!("cannot start `{}`: {}", python, e))?;
+    if !status.success() {
+        return Err(format!("pandas_etl.py exited with {}", status));
+    }
+
+    read_engine_run("pandas", &Path::new(results_dir).join("pandas_metrics.json"))
+}
+
+/// Read an engine's stage timings from a flat metrics JSON it wrote (non-numeric values
+/// are ignored). Err says which file could not be used.
+pub fn read_engine_run(engine: &'static str, path: &Path) -> Result<EngineRun, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let metrics: HashMap<String, serde_json::Value> =
+        serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let metrics: HashMap<String, f64> =
+        metrics.into_iter().filter_map(|(k, v)| v.as_f64().map(|v| (k, v))).collect();
+    Ok(EngineRun::from_metrics(engine, &metrics))
+}
+
+/// DuckDB has no runner in this repo yet; always the reason it is skipped.
+pub fn run_duckdb(_input: &str) -> Result<EngineRun, String> {
+    Err("no DuckDB runner in this repo yet".into())
+}
+
+/// Plain-text table: one row per stage of [`STAGES`], then the multi-stage groups of
+/// [`COMPARED_STAGES`] and the total, with a time column per engine, the speedup of the first
+/// engine over each of the others, and the fastest engine. Stages that don't do the same work
+/// on every engine (Polars' plan-only load and clean) show their times but read "n/c" instead
+/// of a speedup and winner; a note under the table says why.
+pub fn engines_table(runs: &[EngineRun]) -> String {
+    if runs.is_empty() {
+        return String::new();
+    }
+    let others = &runs[1..];
+
+    let mut out = format!("{:<22}", "Stage");
+    for run in runs {
+        out += &format!(" {:>10}", run.engine);
+    }
+    for other in others {
+        out += &format!(" {:>12}", format!("vs {}", other.engine));
+    }
+    out += "  Winner\n";
+    out += &"-".repeat(out.len().saturating_sub(1));
+    out += "\n";
+
+    // (label, comparable, seconds per engine)
+    let stages = STAGES.iter().map(|(key, label, comparable)| {
+        (*label, *comparable, runs.iter().map(|r| r.stages.get(*key).copied()).collect::<Vec<_>>())
+    });
+    let groups = COMPARED_STAGES
+        .iter()
+        .filter(|(keys, _)| keys.len() > 1)
+        .map(|(keys, label)| (*label, true, runs.iter().map(|r| r.group_time(keys)).collect()));
+    let total = std::iter::once(("Total", true, runs.iter().map(|r| Some(r.total())).collect()));
+    for (label, comparable, times) in stages.chain(groups).chain(total) {
+        if times.iter().all(Option::is_none) {
+            continue;
+        }
+        out += &format!("{:<22}", label);
+        for t in &times {
+            out += &match t {
+                Some(t) => format!(" {:>9.2}s", t),
+                None => format!(" {:>10}", "-"),
+            };
+        }
+        for t in &times[1..] {
+            out += &match (times[0], t) {
+                _ if !comparable => format!(" {:>12}", "n/c"),
+                (Some(b), Some(o)) if b > 0.0 => format!(" {:>11.1}x", o / b),
+                _ => format!(" {:>12}", "-"),
+            };
+        }
+        if !comparable {
+            out += "  n/c\n";
+            continue;
+        }
+        let winner = times
+            .iter()
+            .zip(runs)
+            .filter_map(|(t, r)| t.map(|t| (t, r.engine)))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or("-", |(_, engine)| engine);
+        out += &format!("  🏆 {}\n", winner);
+    }
+    out += "\nn/c: Polars' load and clean only plan; it reads and cleans the file inside aggregate and sort &\n\
+            filter, so those two rows carry that work on Polars. \"Load to sort & filter\" compares the same work.\n";
+    out
+}
+
+// ------- speedup narrative -------
+
+/// Groups of stages that do the same work on every engine: (metric keys, summed; label).
+/// Polars' load and clean only build a lazy plan; the file is read and cleaned when
+/// aggregate collects (and again for sort/filter), where pandas does it eagerly in its own
+/// load and clean. Those stages only compare together.
+pub const COMPARED_STAGES: [(&[&str], &str); 2] = [
+    (&["load_time", "clean_time", "aggregate_time", "sort_filter_time"], "Load to sort & filter"),
+    (&["save_time"], "Save"),
+];
+
+/// Polars' run next to one other engine's on the same input.
+#[derive(Clone, Debug)]
+pub struct ComparisonResult {
+    pub polars: EngineRun,
+    pub other: EngineRun,
+}
+
+impl ComparisonResult {
+    /// (label, Polars' seconds, the other engine's seconds) for each group of
+    /// [`COMPARED_STAGES`] both runs recorded in full, in pipeline order.
+    pub fn group_times(&self) -> Vec<(&'static str, f64, f64)> {
+        COMPARED_STAGES
+            .iter()
+            .filter_map(|(keys, label)| Some((*label, self.polars.group_time(keys)?, self.other.group_time(keys)?)))
+            .collect()
+    }
+}
+
+/// Turns a [`ComparisonResult`] into a short plain-language summary: the overall ratio and
+/// the ratio of each group of stages that does the same work on both engines (see
+/// [`COMPARED_STAGES`]). It reports the run's numbers only; it doesn't guess at causes.
+#[derive(Clone, Debug)]
+pub struct SpeedupNarrator {
+    parity_band: f64,       // ratios within 1 ± this count as "about the same"
+    min_stage_seconds: f64, // groups quicker than this on both engines are too noisy to compare
+}
+
+impl Default for SpeedupNarrator {
+    fn default() -> Self {
+        SpeedupNarrator { parity_band: 0.1, min_stage_seconds: 0.05 }
+    }
+}
+
+impl SpeedupNarrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ratios within `1 ± band` (e.g. 0.1) are reported as parity rather than a win.
+    pub fn with_parity_band(mut self, band: f64) -> Self {
+        self.parity_band = band.max(0.0);
+        self
+    }
+
+    /// Leave out stage groups that took less than `secs` on both engines.
+    pub fn with_min_stage_seconds(mut self, secs: f64) -> Self {
+        self.min_stage_seconds = secs.max(0.0);
+        self
+    }
+
+    fn faster(&self, ratio: f64) -> bool {
+        ratio >= 1.0 + self.parity_band
+    }
+
+    fn slower(&self, ratio: f64) -> bool {
+        ratio <= 1.0 / (1.0 + self.parity_band)
+    }
+
+    // "4.1x faster", "2.0x slower" or "about as fast", from Polars' point of view
+    fn verdict(&self, ratio: f64) -> String {
+        if self.faster(ratio) {
+            format!("{:.1}x faster", ratio)
+        } else if self.slower(ratio) {
+            format!("{:.1}x slower", 1.0 / ratio)
+        } else {
+            "about as fast".to_string()
+        }
+    }
+
+    pub fn explain_speedup(&self, comparison: &ComparisonResult) -> String {
+        let other = comparison.other.engine;
+        let (polars_total, other_total) = (comparison.polars.total(), comparison.other.total());
+        if polars_total <= 0.0 || other_total <= 0.0 {
+            return format!("No stage timings to compare Polars with {}.", other);
+        }
+
+        let overall = other_total / polars_total;
+        let mut out = format!(
+            "Polars was {} {} {} overall ({:.2}s vs {:.2}s of stage time).",
+            self.verdict(overall),
+            if self.faster(overall) || self.slower(overall) { "than" } else { "as" },
+            other,
+            polars_total,
+            other_total
+        );
+
+        let (groups, noisy): (Vec<_>, Vec<_>) = comparison
+            .group_times()
+            .into_iter()
+            .partition(|(_, ours, theirs)| ours.max(*theirs) >= self.min_stage_seconds);
+        let compared: Vec<String> = groups
+            .iter()
+            .filter(|(_, ours, _)| *ours > 0.0)
+            .map(|(label, ours, theirs)| {
+                format!("{}: {} ({:.2}s vs {:.2}s)", label, self.verdict(theirs / ours), ours, theirs)
+            })
+            .collect();
+        if !compared.is_empty() {
+            out += &format!(" {}.", compared.join("; "));
+        }
+        if !noisy.is_empty() {
+            let names: Vec<String> = noisy.iter().map(|(label, _, _)| label.to_lowercase()).collect();
+            out += &format!(
+                " Too short to compare (under {:.2}s on both): {}.",
+                self.min_stage_seconds,
+                names.join(", ")
+            );
+        }
+        out += " Load and clean aren't compared on their own: Polars only plans there and reads and cleans the \
+                file in the stages that collect.";
+        out
+    }
+}
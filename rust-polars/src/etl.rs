@@ -1,13 +1,17 @@
+use crate::fingerprint::input_fingerprint;
+use crate::progress::{ProgressEvent, ProgressHook};
+use crate::status;
+use chrono::NaiveDate;
 use polars::prelude::*;
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 // ------- simple Linux RSS (MB) -------
 fn rss_mb() -> f64 {
     if let Ok(s) = std::fs::read_to_string("/proc/self/status") {
         for line in s.lines() {
             if let Some(val) = line.strip_prefix("VmRSS:") {
-                let kb: f64 = val.split_whitespace().nth(0).unwrap_or("0")
+                let kb: f64 = val.split_whitespace().next().unwrap_or("0")
                     .parse().unwrap_or(0.0);
                 return kb / 1024.0;
             }
@@ -25,212 +29,2224 @@ fn bump_peak(metrics: &mut HashMap<String, f64>, label: &str) {
     }
 }
 
+// Number of source scans left in an optimized plan (a cached subplan counts once)
+fn count_scans(lf: &LazyFrame) -> PolarsResult<usize> {
+    let plan = lf.describe_optimized_plan()?;
+    Ok(plan.lines().filter(|l| l.contains("SCAN")).count())
+}
+
+// ------- aggregation specs -------
+#[derive(Clone, Copy, Debug)]
+pub enum Agg {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Mode, // most frequent value; ties go to the smallest
+}
+
+impl std::str::FromStr for Agg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "count" => Ok(Agg::Count),
+            "sum" => Ok(Agg::Sum),
+            "mean" | "avg" => Ok(Agg::Mean),
+            "min" => Ok(Agg::Min),
+            "max" => Ok(Agg::Max),
+            "mode" => Ok(Agg::Mode),
+            other => Err(format!("unknown aggregation '{}'", other)),
+        }
+    }
+}
+
+impl Agg {
+    fn name(self) -> &'static str {
+        match self {
+            Agg::Count => "count",
+            Agg::Sum => "sum",
+            Agg::Mean => "mean",
+            Agg::Min => "min",
+            Agg::Max => "max",
+            Agg::Mode => "mode",
+        }
+    }
+}
+
+/// One measure in the grouped aggregation: `agg(column) AS alias`.
+#[derive(Clone, Debug)]
+pub struct AggSpec {
+    pub column: String,
+    pub agg: Agg,
+    pub alias: String,
+}
+
+impl AggSpec {
+    pub fn new(column: &str, agg: Agg) -> Self {
+        Self {
+            column: column.to_string(),
+            agg,
+            alias: format!("{}_{}", agg.name(), column),
+        }
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = alias.to_string();
+        self
+    }
+
+    /// Parse `column:agg[:alias]`, e.g. `tip_amount:sum:total_tips`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.split(':');
+        let column = parts.next().filter(|c| !c.is_empty());
+        let agg = parts.next();
+        let (Some(column), Some(agg)) = (column, agg) else {
+            return Err(format!("expected column:agg[:alias], got '{}'", spec));
+        };
+        let parsed = AggSpec::new(column, agg.parse()?);
+        Ok(match parts.next() {
+            Some(alias) if !alias.is_empty() => parsed.alias(alias),
+            _ => parsed,
+        })
+    }
+
+    pub(crate) fn to_expr(&self) -> Expr {
+        let c = col(self.column.as_str());
+        let e = match self.agg {
+            Agg::Count => c.count(),
+            Agg::Sum => c.sum(),
+            Agg::Mean => c.mean(),
+            Agg::Min => c.min(),
+            Agg::Max => c.max(),
+            // `mode` keeps every tied value; taking the min makes the pick deterministic
+            Agg::Mode => c.mode().min(),
+        };
+        e.alias(self.alias.as_str())
+    }
+
+    // Sum/mean over whole cents as integers: exact, so the result doesn't depend on how
+    // the rows were split across threads. Other aggregations are order-independent already.
+    fn to_exact_expr(&self) -> Expr {
+        // Round half away from zero (the cast truncates); refunds are negative
+        let scaled = col(self.column.as_str()) * lit(100.0);
+        let cents = when(scaled.clone().gt_eq(lit(0.0)))
+            .then(scaled.clone() + lit(0.5))
+            .otherwise(scaled - lit(0.5))
+            .cast(DataType::Int64);
+        let e = match self.agg {
+            Agg::Sum => cents.sum().cast(DataType::Float64) / lit(100.0),
+            Agg::Mean => {
+                cents.sum().cast(DataType::Float64)
+                    / col(self.column.as_str()).count().cast(DataType::Float64)
+                    / lit(100.0)
+            }
+            _ => return self.to_expr(),
+        };
+        e.alias(self.alias.as_str())
+    }
+}
+
+/// Daily measures computed by default (mirrors the pandas daily stats).
+pub fn default_agg_specs() -> Vec<AggSpec> {
+    vec![
+        AggSpec::new("trip_distance", Agg::Count).alias("trip_count"),
+        AggSpec::new("trip_distance", Agg::Mean).alias("avg_trip_distance"),
+        AggSpec::new("trip_distance", Agg::Sum).alias("total_trip_distance"),
+        AggSpec::new("trip_duration_minutes", Agg::Mean).alias("avg_trip_duration"),
+        AggSpec::new("trip_duration_minutes", Agg::Sum).alias("total_trip_duration"),
+        AggSpec::new("passenger_count", Agg::Sum).alias("total_passengers"),
+        AggSpec::new("total_amount", Agg::Mean).alias("avg_total_amount"),
+        AggSpec::new("total_amount", Agg::Sum).alias("total_revenue"),
+    ]
+}
+
+// ------- schema normalization -------
+// Fare components that always exist in yellow-taxi files (summed into total_amount)
+const FARE_COMPONENTS: [&str; 6] = [
+    "fare_amount",
+    "extra",
+    "mta_tax",
+    "tip_amount",
+    "tolls_amount",
+    "improvement_surcharge",
+];
+
+// Surcharges added in later TLC schema generations; matched case-insensitively
+// (2023+ files spell it `Airport_fee`) and renamed to these canonical names.
+const SURCHARGE_COLUMNS: [&str; 2] = ["congestion_surcharge", "airport_fee"];
+
+// Dollar amounts; summed exactly in cents when deterministic sums are on
+fn is_monetary(column: &str) -> bool {
+    column == "total_amount" || FARE_COMPONENTS.contains(&column) || SURCHARGE_COLUMNS.contains(&column)
+}
+
+// Every column of the yellow-taxi schema generations (matched case-insensitively); anything
+// else in the input is an extra column, see `ExtraColumnPolicy`
+const KNOWN_COLUMNS: [&str; 24] = [
+    "VendorID",
+    "tpep_pickup_datetime",
+    "tpep_dropoff_datetime",
+    "passenger_count",
+    "trip_distance",
+    "pickup_longitude",
+    "pickup_latitude",
+    "RatecodeID",
+    "store_and_fwd_flag",
+    "dropoff_longitude",
+    "dropoff_latitude",
+    "PULocationID",
+    "DOLocationID",
+    "payment_type",
+    "fare_amount",
+    "extra",
+    "mta_tax",
+    "tip_amount",
+    "tolls_amount",
+    "improvement_surcharge",
+    "total_amount",
+    "congestion_surcharge",
+    "airport_fee",
+    "cbd_congestion_fee",
+];
+
+/// What to do with input columns outside the known yellow-taxi schemas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtraColumnPolicy {
+    #[default]
+    Ignore,  // dropped by the load projection
+    Include, // carried through to the cleaned frame
+    Error,   // fail the load
+}
+
+impl std::str::FromStr for ExtraColumnPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ignore" => Ok(ExtraColumnPolicy::Ignore),
+            "include" => Ok(ExtraColumnPolicy::Include),
+            "error" => Ok(ExtraColumnPolicy::Error),
+            other => Err(format!("unknown extra column policy '{}' (ignore|include|error)", other)),
+        }
+    }
+}
+
+// Columns holding decimals (matched case-insensitively); read as text with decimal commas
+fn is_decimal_column(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    is_monetary(&name)
+        || matches!(
+            name.as_str(),
+            "trip_distance" | "pickup_longitude" | "pickup_latitude" | "dropoff_longitude" | "dropoff_latitude"
+        )
+}
+
+// Dtype overrides reading every decimal column among `names` as String. Inference would
+// take a thousands-dot value like "1.234" for the float 1.234 instead of 1234.
+fn decimal_text_overrides<'a>(names: impl IntoIterator<Item = &'a str>) -> SchemaRef {
+    Arc::new(
+        names
+            .into_iter()
+            .filter(|name| is_decimal_column(name))
+            .map(|name| Field::new(name.into(), DataType::String))
+            .collect(),
+    )
+}
+
+// "1.234,56" → 1234.56: drop the thousands dots, then turn the decimal comma into a point
+fn parse_decimal_comma(text: Expr) -> Expr {
+    text.str()
+        .replace_all(lit("."), lit(""), true)
+        .str()
+        .replace_all(lit(","), lit("."), true)
+        .cast(DataType::Float64)
+}
+
+fn detect_extra_columns(schema: &Schema) -> Vec<PlSmallStr> {
+    schema
+        .iter_names()
+        .filter(|name| !KNOWN_COLUMNS.iter().any(|k| name.eq_ignore_ascii_case(k)))
+        .cloned()
+        .collect()
+}
+
+// Cents of rounding slack allowed when re-adding fare components
+const TOTAL_AMOUNT_TOLERANCE: f64 = 0.01;
+
+/// Optional surcharge columns present in `schema`, as (name in file, canonical name).
+fn detect_surcharge_columns(schema: &Schema) -> Vec<(PlSmallStr, &'static str)> {
+    SURCHARGE_COLUMNS
+        .iter()
+        .filter_map(|canonical| {
+            schema
+                .iter_names()
+                .find(|name| name.eq_ignore_ascii_case(canonical))
+                .map(|name| (name.clone(), *canonical))
+        })
+        .collect()
+}
+
+/// Parse a dtype name for `--coerce` (int32|int64|float32|float64|bool|string|date).
+pub fn parse_dtype(s: &str) -> Result<DataType, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "int32" | "i32" => Ok(DataType::Int32),
+        "int64" | "i64" | "int" => Ok(DataType::Int64),
+        "float32" | "f32" => Ok(DataType::Float32),
+        "float64" | "f64" | "float" => Ok(DataType::Float64),
+        "bool" | "boolean" => Ok(DataType::Boolean),
+        "string" | "str" => Ok(DataType::String),
+        "date" => Ok(DataType::Date),
+        other => Err(format!("unknown type '{}' (int32|int64|float32|float64|bool|string|date)", other)),
+    }
+}
+
+// ------- multi-file input -------
+// `*` / `?` matching for a single file-name component
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            wildcard_match(&pattern[1..], name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+pub fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?'])
+}
+
+/// Whether `input` names an existing file/directory, or a glob matching at least one file.
+pub fn input_exists(input: &str) -> bool {
+    if is_glob(input) {
+        expand_glob(input).is_ok_and(|paths| !paths.is_empty())
+    } else {
+        std::path::Path::new(input).exists()
+    }
+}
+
+/// Files matching `pattern`, sorted by path. Wildcards are only honoured in the
+/// file-name part; the directory must be literal.
+pub fn expand_glob(pattern: &str) -> PolarsResult<Vec<String>> {
+    let path = std::path::Path::new(pattern);
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => std::path::Path::new("."),
+    };
+    let file_pattern = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .ok_or_else(|| polars_err!(ComputeError: "invalid input pattern '{}'", pattern))?;
+
+    let mut paths: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .is_some_and(|name| wildcard_match(file_pattern.as_bytes(), name.as_bytes()))
+        })
+        .map(|e| e.path().to_string_lossy().into_owned())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// ------- errors -------
+#[derive(Debug)]
+pub enum EtlError {
+    /// The format's Polars feature was not compiled into this build.
+    FormatNotSupported { format: &'static str },
+    Polars(PolarsError),
+}
+
+impl std::fmt::Display for EtlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EtlError::FormatNotSupported { format } => write!(
+                f,
+                "output format '{}' is not supported by this build (rebuild with `--features {}`)",
+                format,
+                if *format == "arrow" { "ipc" } else { format }
+            ),
+            EtlError::Polars(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EtlError {}
+
+impl From<PolarsError> for EtlError {
+    fn from(e: PolarsError) -> Self {
+        EtlError::Polars(e)
+    }
+}
+
+impl From<std::io::Error> for EtlError {
+    fn from(e: std::io::Error) -> Self {
+        EtlError::Polars(e.into())
+    }
+}
+
+// ------- output formats -------
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Csv,
+    Parquet,
+    Arrow, // Arrow IPC file
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 3] = [OutputFormat::Csv, OutputFormat::Parquet, OutputFormat::Arrow];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Arrow => "arrow",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        self.name()
+    }
+
+    /// Whether the Polars reader/writer for this format was compiled in.
+    pub fn is_supported(self) -> bool {
+        match self {
+            OutputFormat::Csv => true,
+            OutputFormat::Parquet => cfg!(feature = "parquet"),
+            OutputFormat::Arrow => cfg!(feature = "ipc"),
+        }
+    }
+
+    pub fn supported() -> Vec<OutputFormat> {
+        Self::ALL.into_iter().filter(|f| f.is_supported()).collect()
+    }
+
+    pub(crate) fn ensure_supported(self) -> Result<(), EtlError> {
+        if self.is_supported() {
+            Ok(())
+        } else {
+            Err(EtlError::FormatNotSupported { format: self.name() })
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(OutputFormat::Csv),
+            "parquet" => Ok(OutputFormat::Parquet),
+            "arrow" | "ipc" => Ok(OutputFormat::Arrow),
+            other => Err(format!("unknown output format '{}'", other)),
+        }
+    }
+}
+
+/// Write `df` to `path` in the given format.
+pub fn write_frame(df: &mut DataFrame, path: &str, format: OutputFormat) -> Result<(), EtlError> {
+    format.ensure_supported()?;
+    let mut file = std::fs::File::create(path)?;
+    match format {
+        OutputFormat::Csv => CsvWriter::new(&mut file).finish(df)?,
+        #[cfg(feature = "parquet")]
+        OutputFormat::Parquet => {
+            ParquetWriter::new(&mut file).finish(df)?;
+        }
+        #[cfg(feature = "ipc")]
+        OutputFormat::Arrow => IpcWriter::new(&mut file).finish(df)?,
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("checked by ensure_supported"),
+    }
+    Ok(())
+}
+
+/// Read a frame written by [`write_frame`] back into memory.
+pub fn read_frame(path: &str, format: OutputFormat) -> Result<DataFrame, EtlError> {
+    format.ensure_supported()?;
+    let lf = match format {
+        OutputFormat::Csv => LazyCsvReader::new(path).with_has_header(true).finish()?,
+        #[cfg(feature = "parquet")]
+        OutputFormat::Parquet => LazyFrame::scan_parquet(path, Default::default())?,
+        #[cfg(feature = "ipc")]
+        OutputFormat::Arrow => LazyFrame::scan_ipc(path, Default::default())?,
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("checked by ensure_supported"),
+    };
+    Ok(lf.collect()?)
+}
+
+// One result cell as JSON: numbers and strings as-is, dates and times as their display text
+fn json_value(value: AnyValue) -> serde_json::Value {
+    match value {
+        AnyValue::Null => serde_json::Value::Null,
+        AnyValue::Boolean(b) => b.into(),
+        AnyValue::Int8(v) => v.into(),
+        AnyValue::Int16(v) => v.into(),
+        AnyValue::Int32(v) => v.into(),
+        AnyValue::Int64(v) => v.into(),
+        AnyValue::UInt8(v) => v.into(),
+        AnyValue::UInt16(v) => v.into(),
+        AnyValue::UInt32(v) => v.into(),
+        AnyValue::UInt64(v) => v.into(),
+        // NaN and infinities have no JSON form and become null
+        AnyValue::Float32(v) => serde_json::json!(v),
+        AnyValue::Float64(v) => serde_json::json!(v),
+        AnyValue::String(s) => s.into(),
+        AnyValue::StringOwned(s) => s.as_str().into(),
+        other => other.to_string().into(),
+    }
+}
+
+// ------- query optimizations -------
+
+/// The Polars optimizer passes applied to the pipeline's collects. The default is Polars'
+/// own default (all on); it is recorded in the manifest and as the `optimizations` tag, so
+/// timing changes after a Polars upgrade can be told apart from changed settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct OptimizationFlags {
+    pub predicate_pushdown: bool,
+    pub projection_pushdown: bool,
+    pub slice_pushdown: bool,
+    pub comm_subexpr_elim: bool,
+    pub comm_subplan_elim: bool,
+}
+
+impl Default for OptimizationFlags {
+    fn default() -> Self {
+        OptimizationFlags {
+            predicate_pushdown: true,
+            projection_pushdown: true,
+            slice_pushdown: true,
+            comm_subexpr_elim: true,
+            comm_subplan_elim: true,
+        }
+    }
+}
+
+impl OptimizationFlags {
+    pub const NAMES: [&'static str; 5] = [
+        "predicate_pushdown",
+        "projection_pushdown",
+        "slice_pushdown",
+        "comm_subexpr_elim",
+        "comm_subplan_elim",
+    ];
+
+    fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "predicate_pushdown" => Some(&mut self.predicate_pushdown),
+            "projection_pushdown" => Some(&mut self.projection_pushdown),
+            "slice_pushdown" => Some(&mut self.slice_pushdown),
+            "comm_subexpr_elim" => Some(&mut self.comm_subexpr_elim),
+            "comm_subplan_elim" => Some(&mut self.comm_subplan_elim),
+            _ => None,
+        }
+    }
+
+    /// Switch one optimization off by name.
+    pub fn disable(&mut self, name: &str) -> Result<(), String> {
+        let flag = self.flag_mut(name).ok_or_else(|| {
+            format!("unknown optimization '{}' ({})", name, Self::NAMES.join("|"))
+        })?;
+        *flag = false;
+        Ok(())
+    }
+
+    /// `name=on|off` for every flag, comma-separated.
+    pub fn describe(self) -> String {
+        let values = [
+            self.predicate_pushdown,
+            self.projection_pushdown,
+            self.slice_pushdown,
+            self.comm_subexpr_elim,
+            self.comm_subplan_elim,
+        ];
+        Self::NAMES
+            .iter()
+            .zip(values)
+            .map(|(name, on)| format!("{}={}", name, if on { "on" } else { "off" }))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn apply(self, lf: LazyFrame) -> LazyFrame {
+        lf.with_predicate_pushdown(self.predicate_pushdown)
+            .with_projection_pushdown(self.projection_pushdown)
+            .with_slice_pushdown(self.slice_pushdown)
+            .with_comm_subexpr_elim(self.comm_subexpr_elim)
+            .with_comm_subplan_elim(self.comm_subplan_elim)
+    }
+}
+
+// ------- output units -------
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Imperial, // miles, as recorded by the TLC
+    Metric,   // kilometres
+}
+
+const KM_PER_MILE: f64 = 1.609344;
+
+impl Units {
+    pub fn name(self) -> &'static str {
+        match self {
+            Units::Imperial => "imperial",
+            Units::Metric => "metric",
+        }
+    }
+
+    // Distances in the output are scaled on the way out; cleaning/filters stay in miles
+    fn scale_distance(self, lf: LazyFrame) -> LazyFrame {
+        match self {
+            Units::Imperial => lf,
+            Units::Metric => lf.with_columns([col("trip_distance") * lit(KM_PER_MILE)]),
+        }
+    }
+}
+
+impl std::str::FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "imperial" => Ok(Units::Imperial),
+            "metric" => Ok(Units::Metric),
+            other => Err(format!("unknown units '{}' (imperial|metric)", other)),
+        }
+    }
+}
+
+// ------- pipeline stages -------
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Load,
+    Clean,
+    Aggregate,
+    SortFilter,
+    Save,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [Stage::Load, Stage::Clean, Stage::Aggregate, Stage::SortFilter, Stage::Save];
+
+    /// Same names as the progress events.
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Load => "load",
+            Stage::Clean => "clean",
+            Stage::Aggregate => "aggregate",
+            Stage::SortFilter => "sort_filter",
+            Stage::Save => "save",
+        }
+    }
+
+    /// The stage whose output this one works on.
+    pub fn requires(self) -> Option<Stage> {
+        match self {
+            Stage::Load => None,
+            Stage::Clean | Stage::Save => Some(Stage::Load),
+            Stage::Aggregate | Stage::SortFilter => Some(Stage::Clean),
+        }
+    }
+
+    /// Parse a comma-separated stage list into pipeline order, rejecting a stage
+    /// whose prerequisite is not selected.
+    pub fn parse_list(s: &str) -> Result<Vec<Stage>, String> {
+        let mut stages = s
+            .split(',')
+            .map(|name| name.trim().parse())
+            .collect::<Result<Vec<Stage>, _>>()?;
+        stages.sort();
+        stages.dedup();
+        for stage in &stages {
+            if let Some(required) = stage.requires().filter(|r| !stages.contains(r)) {
+                return Err(format!("stage '{}' needs '{}'", stage.name(), required.name()));
+            }
+        }
+        Ok(stages)
+    }
+}
+
+impl std::str::FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Stage::ALL
+            .into_iter()
+            .find(|stage| stage.name() == s)
+            .ok_or_else(|| format!("unknown stage '{}' (load|clean|aggregate|sort_filter|save)", s))
+    }
+}
+
+// Row-level result sets behind `long_trips_count` / `expensive_trips_count`
+fn long_trip() -> Expr {
+    col("trip_distance").gt(lit(10.0))
+}
+
+fn expensive_trip() -> Expr {
+    col("total_amount").gt(lit(50.0))
+}
+
+// Pickup between 22:00 and 06:00; TLC timestamps are already NYC local time
+fn is_night() -> Expr {
+    let hour = col("tpep_pickup_datetime").dt().hour();
+    hour.clone().gt_eq(lit(22)).or(hour.lt(lit(6)))
+}
+
+// Parse the timestamp strings and derive `trip_duration_minutes`
+fn with_trip_times(lf: LazyFrame) -> LazyFrame {
+    // TLC timestamps have no fractional part, so a `%.f` suffix fails to match them and
+    // with `strict: false` every row would parse to null. `exact: false` still accepts
+    // values that carry trailing fractional seconds.
+    let fmt: PlSmallStr = "%Y-%m-%d %H:%M:%S".into();
+    let to_dt_opts = StrptimeOptions {
+        format: Some(fmt),
+        strict: false,   // coerce invalid instead of erroring
+        exact: false,
+        cache: true,
+    };
+
+    lf.with_columns([
+        col("tpep_pickup_datetime").str().strptime(
+            DataType::Datetime(TimeUnit::Microseconds, None),
+            to_dt_opts.clone(),
+            lit("coerce"), // be tolerant
+        ),
+        col("tpep_dropoff_datetime").str().strptime(
+            DataType::Datetime(TimeUnit::Microseconds, None),
+            to_dt_opts,
+            lit("coerce"),
+        ),
+    ])
+    .with_columns([
+        (col("tpep_dropoff_datetime") - col("tpep_pickup_datetime"))
+            .dt()
+            .total_minutes()
+            .alias("trip_duration_minutes"),
+    ])
+}
+
+/// What to do with trips whose dropoff is before the pickup (meter/clock faults). Timestamps
+/// carry the date, so trips across midnight have a positive duration and are not affected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NegativeDurationPolicy {
+    #[default]
+    Drop,      // removed by the duration rule
+    #[serde(rename = "clamp_zero")]
+    ClampZero, // kept with trip_duration_minutes = 0
+    Keep,      // kept as recorded
+}
+
+impl NegativeDurationPolicy {
+    pub fn name(self) -> &'static str {
+        match self {
+            NegativeDurationPolicy::Drop => "drop",
+            NegativeDurationPolicy::ClampZero => "clamp_zero",
+            NegativeDurationPolicy::Keep => "keep",
+        }
+    }
+}
+
+impl std::str::FromStr for NegativeDurationPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "drop" => Ok(NegativeDurationPolicy::Drop),
+            "clampzero" => Ok(NegativeDurationPolicy::ClampZero),
+            "keep" => Ok(NegativeDurationPolicy::Keep),
+            other => Err(format!("unknown negative duration policy '{}' (drop|clamp_zero|keep)", other)),
+        }
+    }
+}
+
+/// Dates a file's pickups should fall in, `[start, end)`. The TLC data has trips dated 2001 or
+/// 2088 by faulty meters; those fall outside the period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeclaredPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DeclaredPeriod {
+    fn month(year: i32, month: u32) -> Option<Self> {
+        let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let end = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        Some(DeclaredPeriod { start, end })
+    }
+
+    /// The month named in a TLC file name, e.g. `yellow_tripdata_2015-01.csv`.
+    pub fn from_file_name(path: &str) -> Option<Self> {
+        let name = std::path::Path::new(path).file_name()?.to_str()?;
+        // First `YYYY-MM` in the name
+        name.as_bytes().windows(7).find_map(|w| {
+            let shape = w.iter().enumerate().all(|(i, b)| if i == 4 { *b == b'-' } else { b.is_ascii_digit() });
+            if !shape {
+                return None;
+            }
+            let w = std::str::from_utf8(w).ok()?;
+            Self::month(w[..4].parse().ok()?, w[5..].parse().ok()?)
+        })
+    }
+
+    /// The period grown by `days` on both sides.
+    pub fn widen(self, days: i64) -> Self {
+        let margin = chrono::Duration::days(days);
+        DeclaredPeriod { start: self.start - margin, end: self.end + margin }
+    }
+
+    /// Pickups inside the period; null for unparsed timestamps.
+    fn contains(self) -> Expr {
+        let at = |d: NaiveDate| {
+            let micros = d.and_hms_opt(0, 0, 0).map_or(0, |t| t.and_utc().timestamp_micros());
+            lit(micros).cast(DataType::Datetime(TimeUnit::Microseconds, None))
+        };
+        col("tpep_pickup_datetime")
+            .gt_eq(at(self.start))
+            .and(col("tpep_pickup_datetime").lt(at(self.end)))
+    }
+}
+
+impl std::fmt::Display for DeclaredPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl std::str::FromStr for DeclaredPeriod {
+    type Err = String;
+
+    /// `YYYY-MM` (that month) or `YYYY-MM-DD..YYYY-MM-DD` (end exclusive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid period '{}' (YYYY-MM or YYYY-MM-DD..YYYY-MM-DD)", s);
+        match s.split_once("..") {
+            Some((start, end)) => {
+                let date = |d: &str| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| invalid());
+                let (start, end) = (date(start)?, date(end)?);
+                if start >= end {
+                    return Err(invalid());
+                }
+                Ok(DeclaredPeriod { start, end })
+            }
+            None => {
+                let (year, month) = s.trim().split_once('-').ok_or_else(invalid)?;
+                let (year, month) = (year.parse().map_err(|_| invalid())?, month.parse().map_err(|_| invalid())?);
+                Self::month(year, month).ok_or_else(invalid)
+            }
+        }
+    }
+}
+
+/// Where the declared period comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PeriodCheck {
+    #[default]
+    Auto, // the month in the input file name, if it has one
+    Off,
+    Fixed(DeclaredPeriod),
+}
+
+impl std::str::FromStr for PeriodCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(PeriodCheck::Auto),
+            "off" | "none" => Ok(PeriodCheck::Off),
+            _ => Ok(PeriodCheck::Fixed(s.parse()?)),
+        }
+    }
+}
+
+/// What to do with trips picked up outside the declared period.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutOfPeriodPolicy {
+    #[default]
+    Drop, // removed by the `out_of_period` cleaning rule
+    Flag, // kept, with an `out_of_period` column
+}
+
+impl OutOfPeriodPolicy {
+    pub fn name(self) -> &'static str {
+        match self {
+            OutOfPeriodPolicy::Drop => "drop",
+            OutOfPeriodPolicy::Flag => "flag",
+        }
+    }
+}
+
+impl std::str::FromStr for OutOfPeriodPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "drop" => Ok(OutOfPeriodPolicy::Drop),
+            "flag" => Ok(OutOfPeriodPolicy::Flag),
+            other => Err(format!("unknown out-of-period policy '{}' (drop|flag)", other)),
+        }
+    }
+}
+
+// Leading rules of `cleaning_rules` that work on the raw columns, before timestamp parsing
+const PRE_PARSE_RULES: usize = 3;
+
+/// Cleaning rules in application order: (name, why a row is dropped, rows to keep).
+/// The first three work on the raw columns, the rest need the parsed trip times. Negative
+/// durations pass the duration rule unless `negative` is `Drop`; `period` adds a rule
+/// dropping pickups outside it.
+pub fn cleaning_rules(
+    negative: NegativeDurationPolicy,
+    period: Option<DeclaredPeriod>,
+) -> Vec<(&'static str, &'static str, Expr)> {
+    let duration = col("trip_duration_minutes");
+    let (duration_reason, duration_ok) = match negative {
+        NegativeDurationPolicy::Drop => (
+            "trip duration not in (0, 480) minutes, or an unparseable timestamp",
+            duration.clone().gt(lit(0)),
+        ),
+        NegativeDurationPolicy::ClampZero | NegativeDurationPolicy::Keep => (
+            "trip duration is 0 or 480+ minutes, or an unparseable timestamp",
+            duration.clone().neq(lit(0)),
+        ),
+    };
+    let mut rules = vec![
+        (
+            "zero_coordinates",
+            "pickup or dropoff longitude/latitude is 0",
+            col("pickup_longitude").neq(lit(0.0))
+                .and(col("pickup_latitude").neq(lit(0.0)))
+                .and(col("dropoff_longitude").neq(lit(0.0)))
+                .and(col("dropoff_latitude").neq(lit(0.0))),
+        ),
+        (
+            "trip_distance_range",
+            "trip_distance not in (0, 100) miles",
+            col("trip_distance").gt(lit(0.0)).and(col("trip_distance").lt(lit(100.0))),
+        ),
+        (
+            "passenger_count_range",
+            "passenger_count not in 1..=6",
+            col("passenger_count").gt(lit(0)).and(col("passenger_count").lt_eq(lit(6))),
+        ),
+        ("trip_duration_range", duration_reason, duration_ok.and(duration.lt(lit(480)))),
+    ];
+    if let Some(period) = period {
+        rules.push(("out_of_period", "pickup date outside the declared period", period.contains()));
+    }
+    rules
+}
+
+// All of `rules` at once
+fn keep_all(rules: &[(&'static str, &'static str, Expr)]) -> Expr {
+    rules.iter().map(|(_, _, keep)| keep.clone()).reduce(|a, b| a.and(b)).unwrap_or(lit(true))
+}
+
+// Percentiles reported by `compute_quantiles`, and the columns they are computed for
+const QUANTILES: [(f64, &str); 3] = [(0.5, "p50"), (0.9, "p90"), (0.99, "p99")];
+const QUANTILE_COLUMNS: [&str; 3] = ["total_amount", "trip_distance", "trip_duration_minutes"];
+/// Rows the approximate quantiles are computed on unless configured otherwise.
+pub const DEFAULT_QUANTILE_SAMPLE_ROWS: usize = 100_000;
+// Seed of the approximate quantiles' sample, so reruns on the same input agree
+const QUANTILE_SAMPLE_SEED: u64 = 42;
+
+/// Environment variable the streaming engine takes its chunk (morsel) size from, in rows.
+/// Polars reads it once per process, so set it before the first streaming collect: at
+/// startup or in a child process.
+pub const MORSEL_SIZE_ENV: &str = "POLARS_IDEAL_MORSEL_SIZE";
+// The engine's chunk size when MORSEL_SIZE_ENV isn't set
+const DEFAULT_MORSEL_ROWS: usize = 100_000;
+
+/// Rows per chunk the streaming engine uses in this process: [`MORSEL_SIZE_ENV`] if it
+/// holds a number, else the engine's default.
+pub fn streaming_morsel_size() -> usize {
+    std::env::var(MORSEL_SIZE_ENV)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MORSEL_ROWS)
+}
+
+// Calibration samples of `auto_sample_for_duration`; two sizes so the fixed per-run cost
+// (scan setup, schema inference) can be told apart from the per-row cost
+const CALIBRATION_ROWS: [usize; 2] = [10_000, 40_000];
+
+// A trip whose fare per minute is this many times its pickup hour's median counts as surge
+const SURGE_THRESHOLD: f64 = 2.0;
+
+fn trip_sets() -> [(&'static str, Expr); 2] {
+    [("long_trips", long_trip()), ("expensive_trips", expensive_trip())]
+}
+
 // ------- ETL -------
+#[derive(Clone)]
 pub struct PolarsETL {
-    df: Option<LazyFrame>,              // current lazy plan (raw or cleaned)
-    metrics: HashMap<String, f64>,
+    pub(crate) df: Option<LazyFrame>,   // current lazy plan (raw or cleaned)
+    pub(crate) metrics: HashMap<String, f64>,
+    pub(crate) tags: HashMap<String, String>, // non-numeric run info (label, ...), saved with the metrics
+    pub(crate) agg_specs: Vec<AggSpec>, // daily measures, computed in one group_by pass
+    sample_size: Option<usize>,         // read only the first N rows of the CSV
+    pub(crate) input_path: Option<String>, // last single-file input, for the load comparisons
+    pub(crate) results: Vec<(&'static str, DataFrame)>, // aggregated outputs (file stem, frame)
+    surcharge_columns: Vec<&'static str>, // optional surcharges found in the input
+    file_read_concurrency: usize,       // parallel readers for load_glob / load_parquet_dir
+    progress: Option<ProgressHook>, // stage events for live consumers
+    hot_columns: Vec<String>,           // columns to keep materialized after cleaning
+    hot: Option<DataFrame>,             // the pinned hot columns, see `pin_hot_columns`
+    write_trip_sets: bool,              // also save the long/expensive trip rows
+    units: Units,                       // distance units of the saved results
+    deterministic_sums: bool,           // exact cent sums for monetary columns
+    extra_columns: ExtraColumnPolicy,   // input columns outside the known schemas
+    decimal_comma: bool,                // CSV numbers written as 1.234,56
+    separator: u8,                      // CSV field separator
+    coerce_types: HashMap<String, DataType>, // explicit casts applied right after load
+    streaming: bool,                    // run the heavy collects on the streaming engine
+    reorder_columns: Option<Vec<String>>, // columns to put first after load
+    use_columns: Option<Vec<String>>,   // the only input columns to read (None = all the pipeline uses)
+    raw: Option<LazyFrame>,             // the loaded plan before cleaning, for the audit
+    negative_duration_policy: NegativeDurationPolicy, // dropoff-before-pickup trips
+    approximate_quantiles: bool,        // quantiles from a random sample instead of every row
+    quantile_sample_rows: usize,        // target sample size for the approximate quantiles
+    optimizations: OptimizationFlags,   // optimizer passes for the stage collects
+    period_check: PeriodCheck,          // where the declared period comes from
+    period_margin_days: i64,            // slack on both sides of the declared period
+    out_of_period: OutOfPeriodPolicy,   // drop or flag pickups outside it
+    period: Option<DeclaredPeriod>,     // the period the last clean applied, margin included
+}
+
+impl Default for PolarsETL {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl PolarsETL {
-    pub fn new() -> Self {
-        Self { df: None, metrics: HashMap::new() }
+impl PolarsETL {
+    pub fn new() -> Self {
+        Self {
+            df: None,
+            metrics: HashMap::new(),
+            tags: HashMap::new(),
+            agg_specs: default_agg_specs(),
+            sample_size: None,
+            input_path: None,
+            results: Vec::new(),
+            surcharge_columns: Vec::new(),
+            file_read_concurrency: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            progress: None,
+            hot_columns: Vec::new(),
+            hot: None,
+            write_trip_sets: false,
+            units: Units::Imperial,
+            deterministic_sums: false,
+            extra_columns: ExtraColumnPolicy::Ignore,
+            decimal_comma: false,
+            separator: b',',
+            coerce_types: HashMap::new(),
+            streaming: false,
+            reorder_columns: None,
+            use_columns: None,
+            raw: None,
+            negative_duration_policy: NegativeDurationPolicy::default(),
+            approximate_quantiles: false,
+            quantile_sample_rows: DEFAULT_QUANTILE_SAMPLE_ROWS,
+            optimizations: OptimizationFlags::default(),
+            period_check: PeriodCheck::Auto,
+            period_margin_days: 1,
+            out_of_period: OutOfPeriodPolicy::Drop,
+            period: None,
+        }
+    }
+
+    /// Limit the scan to the first `n` CSV rows (used by the web demo).
+    pub fn with_sample_size(mut self, n: Option<usize>) -> Self {
+        self.sample_size = n;
+        self
+    }
+
+    /// Free-form label (hardware, config, ...) stored as `label` in the metrics JSON.
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        match label {
+            Some(label) => self.tags.insert("label".into(), label),
+            None => self.tags.remove("label"),
+        };
+        self
+    }
+
+    /// Replace the daily measures; all specs are evaluated in a single `agg([...])`.
+    pub fn with_agg_specs(mut self, specs: Vec<AggSpec>) -> Self {
+        self.agg_specs = specs;
+        self
+    }
+
+    /// Report stage boundaries (start and finish) to `hook`.
+    pub fn with_progress(mut self, hook: ProgressHook) -> Self {
+        self.progress = Some(hook);
+        self
+    }
+
+    /// Columns `pin_hot_columns` keeps in memory; everything else stays lazy.
+    pub fn with_hot_columns(mut self, columns: Vec<String>) -> Self {
+        self.hot_columns = columns;
+        self
+    }
+
+    /// Also write the rows behind `long_trips_count` and `expensive_trips_count` to
+    /// `long_trips.parquet` / `expensive_trips.parquet` when saving.
+    pub fn with_trip_sets(mut self, enabled: bool) -> Self {
+        self.write_trip_sets = enabled;
+        self
+    }
+
+    /// Report distances in the results as miles (default) or kilometres; recorded as `units`.
+    pub fn with_units(mut self, units: Units) -> Self {
+        self.units = units;
+        self.tags.insert("units".into(), units.name().into());
+        self
+    }
+
+    /// Sum monetary columns as integer cents so totals are bit-identical whatever the
+    /// thread count (slightly slower). Recorded as `summation_strategy`.
+    pub fn with_deterministic_sums(mut self, enabled: bool) -> Self {
+        self.deterministic_sums = enabled;
+        let strategy = if enabled { "exact_cents" } else { "parallel" };
+        self.tags.insert("summation_strategy".into(), strategy.into());
+        self
+    }
+
+    /// Ignore (default), keep or reject input columns outside the known yellow-taxi schemas.
+    pub fn with_extra_columns(mut self, policy: ExtraColumnPolicy) -> Self {
+        self.extra_columns = policy;
+        self
+    }
+
+    /// How cleaning treats trips whose dropoff is before the pickup: drop (default), clamp
+    /// the duration to 0 or keep it. Recorded as `negative_duration_policy`; the number of
+    /// such rows is recorded as `negative_duration_rows` whatever the policy.
+    pub fn with_negative_duration_policy(mut self, policy: NegativeDurationPolicy) -> Self {
+        self.negative_duration_policy = policy;
+        self.tags.insert("negative_duration_policy".into(), policy.name().into());
+        self
+    }
+
+    /// Compute `compute_quantiles` on a seeded random sample of `quantile_sample_rows` rows
+    /// instead of the whole frame (exact anyway when the frame is that small).
+    pub fn with_approximate_quantiles(mut self, enabled: bool) -> Self {
+        self.approximate_quantiles = enabled;
+        self
+    }
+
+    /// Optimizer passes for the stage collects. Recorded as the `optimizations` tag
+    /// (`predicate_pushdown=on,...`).
+    pub fn with_optimizations(mut self, flags: OptimizationFlags) -> Self {
+        self.optimizations = flags;
+        self.tags.insert("optimizations".into(), flags.describe());
+        self
+    }
+
+    pub fn optimizations(&self) -> OptimizationFlags {
+        self.optimizations
+    }
+
+    /// Period the pickups should fall in (default: the month in the file name) and how many
+    /// days of slack to allow on each side. Recorded as `declared_period` when one applies.
+    pub fn with_declared_period(mut self, check: PeriodCheck, margin_days: i64) -> Self {
+        self.period_check = check;
+        self.period_margin_days = margin_days.max(0);
+        self
+    }
+
+    /// Drop (default) or flag pickups outside the declared period.
+    pub fn with_out_of_period(mut self, policy: OutOfPeriodPolicy) -> Self {
+        self.out_of_period = policy;
+        self.tags.insert("out_of_period_policy".into(), policy.name().into());
+        self
+    }
+
+    // The declared period with its margin, if any applies to this input
+    fn declared_period(&self) -> Option<DeclaredPeriod> {
+        let period = match self.period_check {
+            PeriodCheck::Auto => self.input_path.as_deref().and_then(DeclaredPeriod::from_file_name),
+            PeriodCheck::Off => None,
+            PeriodCheck::Fixed(period) => Some(period),
+        };
+        period.map(|p| p.widen(self.period_margin_days))
+    }
+
+    // The period whose rule drops rows, for the audit
+    fn dropped_period(&self) -> Option<DeclaredPeriod> {
+        self.period.filter(|_| self.out_of_period == OutOfPeriodPolicy::Drop)
+    }
+
+    /// Sample size for approximate quantiles: larger is more accurate and slower.
+    pub fn with_quantile_sample_rows(mut self, rows: usize) -> Self {
+        self.quantile_sample_rows = rows.max(1);
+        self
+    }
+
+    /// Read CSV numbers in the European style: `,` as the decimal separator and `.` for
+    /// thousands. The decimal columns are read as text and parsed during load. Recorded as
+    /// `decimal_format` (`point` or `comma`).
+    pub fn with_decimal_comma(mut self, enabled: bool) -> Self {
+        self.decimal_comma = enabled;
+        let format = if enabled { "comma" } else { "point" };
+        self.tags.insert("decimal_format".into(), format.into());
+        self
+    }
+
+    /// CSV field separator (default `,`; European exports usually use `;`). Recorded as
+    /// the `csv_separator` tag.
+    pub fn with_separator(mut self, separator: u8) -> Self {
+        self.separator = separator;
+        self.tags.insert("csv_separator".into(), (separator as char).to_string());
+        self
+    }
+
+    /// Cast these columns right after load instead of trusting inference. Values that
+    /// don't cast become null and are counted as `coerce_failures_<column>`.
+    pub fn with_coerce_types(mut self, types: HashMap<String, DataType>) -> Self {
+        self.coerce_types = types;
+        self
+    }
+
+    /// Run the aggregation and sort/filter collects on the streaming engine. Its chunk size
+    /// is per process, see [`streaming_morsel_size`]; the size in effect is recorded as
+    /// `streaming_chunk_size`, so `peak_memory_mb` can be compared across sizes. Needs the
+    /// `streaming` cargo feature.
+    pub fn with_streaming(mut self, enabled: bool) -> Self {
+        self.streaming = enabled;
+        if enabled {
+            self.metrics.insert("streaming_chunk_size".into(), streaming_morsel_size() as f64);
+        } else {
+            self.metrics.remove("streaming_chunk_size");
+        }
+        self
+    }
+
+    /// Put these columns first (in this order) right after load, e.g. the ones the
+    /// aggregation reads together; the rest keep their order. Recorded as `column_order`.
+    pub fn with_reorder_columns(mut self, columns: Option<Vec<String>>) -> Self {
+        self.reorder_columns = columns;
+        self
+    }
+
+    /// Read only these input columns (matched case-insensitively, recorded as the
+    /// `use_columns` tag), e.g. the 4 an `--agg` query needs out of 19, so the rest are
+    /// never parsed and `validate_schema` only checks these. Cleaning rules on columns left
+    /// out keep every row; a stage or measure that needs one fails naming it. The clean
+    /// stage always needs both pickup and dropoff timestamps.
+    pub fn with_use_columns(mut self, columns: Option<Vec<String>>) -> Self {
+        self.use_columns = columns;
+        self
+    }
+
+    // The plan with the configured optimizer passes
+    pub(crate) fn optimized(&self, lf: LazyFrame) -> LazyFrame {
+        self.optimizations.apply(lf)
+    }
+
+    // Collect with the configured optimizations on the engine picked by `with_streaming`
+    pub(crate) fn collect_plan(&self, lf: LazyFrame) -> PolarsResult<DataFrame> {
+        let lf = self.optimized(lf);
+        #[cfg(feature = "streaming")]
+        if self.streaming {
+            return lf.with_new_streaming(true).collect();
+        }
+        lf.collect()
+    }
+
+    // Expression for one measure, honouring the summation strategy
+    pub(crate) fn agg_expr(&self, spec: &AggSpec) -> Expr {
+        if self.deterministic_sums && is_monetary(&spec.column) {
+            spec.to_exact_expr()
+        } else {
+            spec.to_expr()
+        }
+    }
+
+    pub(crate) fn emit(&self, event: ProgressEvent) {
+        if let Some(progress) = &self.progress {
+            progress(&event);
+        }
+    }
+
+    /// Read at most `n` files at once in the multi-file loaders (default: available cores).
+    pub fn with_file_read_concurrency(mut self, n: usize) -> Self {
+        self.file_read_concurrency = n.max(1);
+        self
+    }
+
+    // CSV reader with the benchmark's options; datetimes are parsed later.
+    pub(crate) fn csv_reader(&self, file_path: &str) -> LazyCsvReader {
+        let separator = self.separator;
+        LazyCsvReader::new(file_path)
+            .with_has_header(true)
+            .with_n_rows(self.sample_size)
+            .with_infer_schema_length(Some(2000))
+            .map_parse_options(|opts| opts.with_try_parse_dates(false).with_separator(separator))
+    }
+
+    fn csv_scan(&self, file_path: &str) -> PolarsResult<LazyFrame> {
+        if !self.decimal_comma {
+            return self.csv_reader(file_path).finish();
+        }
+        let schema = self.csv_reader(file_path).finish()?.collect_schema()?;
+        let overrides = decimal_text_overrides(schema.iter_names().map(|name| name.as_str()));
+        self.csv_reader(file_path).with_dtype_overwrite(Some(overrides)).finish()
+    }
+
+    // Columns the pipeline works on, normalized across schema generations. Also records
+    // which optional surcharge and unknown extra columns the input has.
+    fn projection(&mut self, schema: &Schema) -> PolarsResult<Vec<Expr>> {
+        let extras = detect_extra_columns(schema);
+        self.metrics.insert("extra_columns_found".into(), extras.len() as f64);
+        if !extras.is_empty() {
+            let names: Vec<&str> = extras.iter().map(|c| c.as_str()).collect();
+            self.tags.insert("extra_columns".into(), names.join(","));
+            if self.extra_columns == ExtraColumnPolicy::Error {
+                polars_bail!(SchemaMismatch: "unexpected input columns: {}", names.join(", "));
+            }
+        }
+
+        let mut surcharges = detect_surcharge_columns(schema);
+
+        // Decimal-comma numbers are read as text (see `csv_scan`) and parsed here
+        let decimal_comma = self.decimal_comma;
+        let number = |name: &str| match schema.get(name) {
+            Some(DataType::String) if decimal_comma => parse_decimal_comma(col(name)).alias(name),
+            _ => col(name),
+        };
+        if !decimal_comma && matches!(schema.get("trip_distance"), Some(DataType::String)) {
+            status!("⚠️  trip_distance was read as text; if the file uses decimal commas, pass --decimal-comma");
+        }
+
+        // Parquet stores typed timestamps; render them like the CSV text so cleaning is shared
+        let timestamp = |name: &str| match schema.get(name) {
+            Some(DataType::Datetime(_, _)) => col(name).dt().to_string("%Y-%m-%d %H:%M:%S"),
+            _ => col(name),
+        };
+
+        let mut columns = vec![
+            number("pickup_longitude"),
+            number("pickup_latitude"),
+            number("dropoff_longitude"),
+            number("dropoff_latitude"),
+            number("trip_distance"),
+            col("passenger_count"),
+            timestamp("tpep_pickup_datetime"),
+            timestamp("tpep_dropoff_datetime"),
+            number("total_amount"),
+        ];
+        // Components a file lacks are left out; the total_amount check is then skipped
+        let (present, missing): (Vec<&str>, Vec<&str>) =
+            FARE_COMPONENTS.iter().copied().partition(|c| schema.get(c).is_some());
+        if missing.is_empty() {
+            self.tags.remove("missing_fare_components");
+        } else {
+            status!("⚠️  No {} column; skipping the total_amount consistency check", missing.join(", "));
+            self.tags.insert("missing_fare_components".into(), missing.join(","));
+        }
+        columns.extend(present.into_iter().map(|c| number(c)));
+        // Older records leave the surcharges empty; treat that as no surcharge
+        columns.extend(surcharges.iter().map(|(name, canonical)| {
+            number(name.as_str())
+                .cast(DataType::Float64)
+                .fill_null(lit(0.0))
+                .alias(*canonical)
+        }));
+        // Payment type for the mode measures; zone IDs (2016-07+ schema) for the join
+        // benchmark and the per-zone payment mode
+        columns.extend(
+            ["payment_type", "PULocationID", "DOLocationID"]
+                .into_iter()
+                .filter(|c| schema.get(c).is_some())
+                .map(col),
+        );
+        if self.extra_columns == ExtraColumnPolicy::Include {
+            columns.extend(extras.into_iter().map(col));
+        }
+
+        if let Some(wanted) = &self.use_columns {
+            // The file's own spelling of each wanted column
+            let mut keep: Vec<PlSmallStr> = Vec::with_capacity(wanted.len());
+            for name in wanted {
+                match schema.iter_names().find(|c| c.eq_ignore_ascii_case(name)) {
+                    Some(c) => keep.push(c.clone()),
+                    None => polars_bail!(ColumnNotFound: "use_columns: '{}' is not in the input", name),
+                }
+            }
+            columns.retain(|e| e.clone().meta().root_names().iter().all(|root| keep.contains(root)));
+            // Wanted columns outside the pipeline's projection (e.g. an extra column) come in as-is
+            let read: Vec<PlSmallStr> = columns.iter().flat_map(|e| e.clone().meta().root_names()).collect();
+            columns.extend(keep.iter().filter(|c| !read.contains(c)).cloned().map(col));
+            let names: Vec<&str> = keep.iter().map(|c| c.as_str()).collect();
+            self.tags.insert("use_columns".into(), names.join(","));
+            // Surcharges left out get no daily total and no place in the total_amount check
+            surcharges.retain(|(name, _)| keep.contains(name));
+        }
+        self.surcharge_columns = surcharges.iter().map(|(_, canonical)| *canonical).collect();
+        self.metrics.insert(
+            "has_surcharge_columns".into(),
+            if self.surcharge_columns.is_empty() { 0.0 } else { 1.0 },
+        );
+        self.metrics.insert("columns_used".into(), columns.len() as f64);
+        Ok(columns)
+    }
+
+    // `cleaning_rules` for the columns `raw` has, and the names of those skipped: a rule on a
+    // column `use_columns` left out keeps every row, so the rule order (and the audit) holds
+    fn loaded_cleaning_rules(
+        &self,
+        raw: &LazyFrame,
+    ) -> PolarsResult<(Vec<(&'static str, &'static str, Expr)>, Vec<&'static str>)> {
+        let schema = raw.clone().collect_schema()?;
+        let mut skipped = Vec::new();
+        let rules = cleaning_rules(self.negative_duration_policy, self.dropped_period())
+            .into_iter()
+            .map(|(name, reason, keep)| {
+                let loaded = keep.clone().meta().root_names().iter().all(|root| {
+                    // derived from the timestamps, which cleaning requires
+                    root == "trip_duration_minutes" || schema.get(root).is_some()
+                });
+                if loaded {
+                    (name, reason, keep)
+                } else {
+                    skipped.push(name);
+                    (name, reason, lit(true))
+                }
+            })
+            .collect();
+        Ok((rules, skipped))
     }
 
     pub fn load_data(&mut self, file_path: &str) -> PolarsResult<&mut Self> {
-        println!("Loading data...");
+        status!("Loading data...");
+        self.emit(ProgressEvent::StageStarted { stage: "load" });
         let start = Instant::now();
 
-        // Lazy scan; parse datetimes explicitly later.
-        let lf = LazyCsvReader::new(file_path)
+        let mut scan = self.csv_scan(file_path)?;
+        self.input_path = Some(file_path.to_string());
+        // Header + inference rows only; tells us which schema generation this file is
+        let schema = scan.collect_schema()?;
+        let columns = self.projection(&schema)?;
+        self.df = Some(scan.select(columns));
+        self.coerce_columns()?;
+        self.reorder_loaded_columns()?;
+        if let Some(n) = self.sample_size {
+            self.metrics.insert("sample_size".into(), n as f64);
+        }
+
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("load_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "load", seconds: t });
+        bump_peak(&mut self.metrics, "after_load");
+        status!("✅ Data scan created in {:.2}s", t);
+        Ok(self)
+    }
+
+    /// Load about `len` bytes of the CSV at `path` starting at byte `start`, for a quick sample
+    /// from anywhere in a large file. The partial lines at either end of the range are
+    /// dropped and the rest is parsed with the file's header. Records `bytes_sampled` and the
+    /// `byte_range` tag (the range read, clipped to the file); `sample_size` does not apply.
+    pub fn load_byte_range(&mut self, path: &str, start: u64, len: u64) -> PolarsResult<&mut Self> {
+        use std::io::{BufRead, Read, Seek, SeekFrom};
+
+        status!("Loading bytes {}..{} of {}...", start, start.saturating_add(len), path);
+        self.emit(ProgressEvent::StageStarted { stage: "load" });
+        let t0 = Instant::now();
+
+        let file = std::fs::File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut file = std::io::BufReader::new(file);
+        let mut buf = Vec::new();
+        let header_len = file.read_until(b'\n', &mut buf)? as u64;
+
+        // A range starting inside the header starts at the first record instead, and none
+        // reaches past the end of the file
+        let from = start.max(header_len).min(file_len);
+        let to = from.saturating_add(len).min(file_len);
+        let mut body = Vec::new();
+        file.seek(SeekFrom::Start(from.saturating_sub(1)))?;
+        file.take(len.saturating_add(1)).read_to_end(&mut body)?;
+
+        // body[0] is the byte before the range: a newline there means the range starts on a
+        // line boundary, otherwise the first (partial) line goes
+        let first = match body.first() {
+            Some(b'\n') => 1,
+            Some(_) => body.iter().position(|b| *b == b'\n').map_or(body.len(), |i| i + 1),
+            None => 0,
+        };
+        let last = body.iter().rposition(|b| *b == b'\n').map_or(first, |i| i + 1).max(first);
+        let sampled = &body[first..last];
+        buf.extend_from_slice(sampled);
+
+        let separator = self.separator;
+        let overrides = self.decimal_comma.then(|| {
+            let header = String::from_utf8_lossy(&buf[..header_len as usize]);
+            decimal_text_overrides(header.trim_end().split(separator as char).map(|name| name.trim_matches('"')))
+        });
+        let df = CsvReadOptions::default()
             .with_has_header(true)
             .with_infer_schema_length(Some(2000))
-            .map_parse_options(|opts| opts.with_try_parse_dates(false))
-            .finish()?
-            .select([
-                col("pickup_longitude"),
-                col("pickup_latitude"),
-                col("dropoff_longitude"),
-                col("dropoff_latitude"),
-                col("trip_distance"),
-                col("passenger_count"),
-                col("tpep_pickup_datetime"),
-                col("tpep_dropoff_datetime"),
-                col("total_amount"),
-            ]);
-
-        self.df = Some(lf);
+            .with_schema_overwrite(overrides)
+            .map_parse_options(|opts| opts.with_try_parse_dates(false).with_separator(separator))
+            .into_reader_with_file_handle(std::io::Cursor::new(buf))
+            .finish()?;
+        self.input_path = Some(path.to_string());
+        let columns = self.projection(df.schema())?;
+        self.df = Some(df.lazy().select(columns));
+        self.coerce_columns()?;
+        self.reorder_loaded_columns()?;
+        self.metrics.insert("bytes_sampled".into(), sampled.len() as f64);
+        self.tags.insert("byte_range".into(), format!("{}..{}", from, to));
+
+        let t = t0.elapsed().as_secs_f64();
+        self.metrics.insert("load_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "load", seconds: t });
+        bump_peak(&mut self.metrics, "after_load");
+        status!("✅ Parsed {} sampled bytes in {:.2}s", sampled.len(), t);
+        Ok(self)
+    }
+
+    /// Load `input` as a single CSV or Parquet file, a CSV glob or a directory of Parquet files.
+    /// Records the `input_fingerprint` tag.
+    pub fn load_input(&mut self, input: &str) -> Result<&mut Self, EtlError> {
+        if let Ok(fingerprint) = input_fingerprint(input) {
+            self.tags.insert("input_fingerprint".into(), fingerprint);
+        }
+        if is_glob(input) {
+            Ok(self.load_glob(input)?)
+        } else if std::path::Path::new(input).is_dir() {
+            self.load_parquet_dir(input)
+        } else if input.ends_with(".parquet") {
+            self.load_parquet_files(&[input.to_string()])
+        } else {
+            Ok(self.load_data(input)?)
+        }
+    }
+
+    /// Pick the `sample_size` whose load → sort_filter run should take about `target`: time the
+    /// core stages on two small samples of `input` (in a separate instance, so no progress
+    /// events or metrics leak into this run), fit fixed cost + per-row cost, and solve for the
+    /// row count. Records `budget_target_time`, `budget_sample_rows` and
+    /// `budget_calibration_time`; the actual time is the stage times of the run that follows.
+    /// Returns the chosen size.
+    pub fn auto_sample_for_duration(&mut self, input: &str, target: Duration) -> Result<usize, EtlError> {
+        status!("Calibrating for a {:.1}s budget...", target.as_secs_f64());
+        let start = Instant::now();
+
+        let mut times = [0.0; 2];
+        for (time, rows) in times.iter_mut().zip(CALIBRATION_ROWS) {
+            // Everything as configured, so the probe runs the same pipeline, but silent
+            let mut probe = PolarsETL { sample_size: Some(rows), progress: None, ..self.clone() };
+            let t = Instant::now();
+            probe.load_input(input)?;
+            probe.clean_data()?.aggregate_data()?.sort_and_filter()?;
+            *time = t.elapsed().as_secs_f64();
+        }
+
+        let [small, large] = CALIBRATION_ROWS.map(|n| n as f64);
+        let per_row = match (times[1] - times[0]) / (large - small) {
+            slope if slope > 0.0 => slope,
+            _ => times[1] / large, // noise swamped the difference; assume no fixed cost
+        };
+        let fixed = (times[0] - per_row * small).max(0.0);
+        let rows = (((target.as_secs_f64() - fixed) / per_row) as usize).max(CALIBRATION_ROWS[0]);
+
+        self.sample_size = Some(rows);
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("budget_target_time".into(), target.as_secs_f64());
+        self.metrics.insert("budget_sample_rows".into(), rows as f64);
+        self.metrics.insert("budget_calibration_time".into(), t);
+        status!(
+            "✅ ~{:.2}µs/row + {:.2}s fixed → {} rows for {:.1}s (calibrated in {:.2}s)",
+            per_row * 1e6,
+            fixed,
+            rows,
+            target.as_secs_f64(),
+            t
+        );
+        Ok(rows)
+    }
+
+    /// Load every CSV matching `pattern` (wildcards in the file name only, e.g.
+    /// `../data/yellow_tripdata_2015-*.csv`). `sample_size` applies per file.
+    pub fn load_glob(&mut self, pattern: &str) -> PolarsResult<&mut Self> {
+        let paths = expand_glob(pattern)?;
+        self.load_files(&paths, |etl, path| etl.csv_scan(path))
+    }
+
+    /// Load every `*.parquet` file in `dir`.
+    pub fn load_parquet_dir(&mut self, dir: &str) -> Result<&mut Self, EtlError> {
+        let paths = expand_glob(&format!("{}/*.parquet", dir.trim_end_matches('/')))?;
+        self.load_parquet_files(&paths)
+    }
+
+    fn load_parquet_files(&mut self, paths: &[String]) -> Result<&mut Self, EtlError> {
+        OutputFormat::Parquet.ensure_supported()?;
+        #[cfg(feature = "parquet")]
+        self.load_files(paths, |etl, path| {
+            let args = ScanArgsParquet { n_rows: etl.sample_size, ..Default::default() };
+            LazyFrame::scan_parquet(path, args)
+        })?;
+        #[cfg(not(feature = "parquet"))]
+        let _ = paths;
+        Ok(self)
+    }
+
+    // Scan each file lazily and select one projection built from the union of their schemas,
+    // so files that differ (e.g. one lacks a surcharge) still line up: a column a file lacks
+    // comes in as typed nulls. Unions of up to `file_read_concurrency` files run in parallel
+    // and the groups run one after another, capping the readers in flight; rows stay in path
+    // order either way.
+    fn load_files<F>(&mut self, paths: &[String], scan: F) -> PolarsResult<&mut Self>
+    where
+        F: Fn(&Self, &str) -> PolarsResult<LazyFrame>,
+    {
+        status!("Loading {} files...", paths.len());
+        self.emit(ProgressEvent::StageStarted { stage: "load" });
+        let start = Instant::now();
+
+        if paths.is_empty() {
+            polars_bail!(ComputeError: "no input files matched");
+        }
+        // Columns in first-seen order, each with the dtype of the first file that has it
+        let mut scans = Vec::with_capacity(paths.len());
+        let mut combined = Schema::default();
+        for path in paths {
+            let mut lf = scan(self, path)?;
+            let schema = lf.collect_schema()?;
+            for (name, dtype) in schema.iter() {
+                if combined.get(name).is_none() {
+                    combined.with_column(name.clone(), dtype.clone());
+                }
+            }
+            scans.push((lf, schema));
+        }
+        // Built once, so the surcharge and column metrics describe the combined input
+        let columns = self.projection(&combined)?;
+        let files: Vec<LazyFrame> = scans
+            .into_iter()
+            .map(|(lf, schema)| {
+                let missing: Vec<Expr> = combined
+                    .iter()
+                    .filter(|(name, _)| schema.get(name).is_none())
+                    .map(|(name, dtype)| lit(Null {}).cast(dtype.clone()).alias(name.clone()))
+                    .collect();
+                let lf = if missing.is_empty() { lf } else { lf.with_columns(missing) };
+                lf.select(columns.clone())
+            })
+            .collect();
+
+        let workers = self.file_read_concurrency.min(paths.len());
+        let parallel = UnionArgs { parallel: true, to_supertypes: true, ..Default::default() };
+        let groups = files
+            .chunks(workers)
+            .map(|group| concat(group.to_vec(), parallel.clone()))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let sequential = UnionArgs { parallel: false, ..parallel };
+        self.df = Some(concat(groups, sequential)?);
+        self.coerce_columns()?;
+        self.reorder_loaded_columns()?;
+
+        self.metrics.insert("files_read".into(), paths.len() as f64);
+        self.metrics.insert("files_read_parallel".into(), workers as f64);
+        if let Some(n) = self.sample_size {
+            self.metrics.insert("sample_size".into(), n as f64);
+        }
 
         let t = start.elapsed().as_secs_f64();
         self.metrics.insert("load_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "load", seconds: t });
         bump_peak(&mut self.metrics, "after_load");
-        println!("✅ Data scan created in {:.2}s", t);
+        status!("✅ Loaded {} files ({} at a time) in {:.2}s", paths.len(), workers, t);
+        Ok(self)
+    }
+
+    // Apply `coerce_types` to the loaded plan. Counting the failed values (non-null before,
+    // null after the non-strict cast) takes one pass over the configured columns.
+    fn coerce_columns(&mut self) -> PolarsResult<()> {
+        let Some(df) = self.df.clone() else { return Ok(()) };
+        if self.coerce_types.is_empty() {
+            return Ok(());
+        }
+        let mut coerce: Vec<(&String, &DataType)> = self.coerce_types.iter().collect();
+        coerce.sort_by_key(|(name, _)| *name);
+
+        let failures = self
+            .optimized(df.clone().select(
+                coerce
+                    .iter()
+                    .map(|(name, dtype)| {
+                        let c = col(name.as_str());
+                        c.clone()
+                            .is_not_null()
+                            .and(c.cast((*dtype).clone()).is_null())
+                            .cast(DataType::Int64)
+                            .sum()
+                            .alias(name.as_str())
+                    })
+                    .collect::<Vec<_>>(),
+            ))
+            .collect()?;
+        for column in failures.get_columns() {
+            let n = column.i64()?.get(0).unwrap_or(0);
+            if n > 0 {
+                status!("⚠️  {} value(s) in '{}' could not be cast and became null", n, column.name());
+            }
+            self.metrics.insert(format!("coerce_failures_{}", column.name()), n as f64);
+        }
+
+        let casts: Vec<Expr> = coerce
+            .iter()
+            .map(|(name, dtype)| col(name.as_str()).cast((*dtype).clone()))
+            .collect();
+        self.df = Some(df.with_columns(casts));
+        Ok(())
+    }
+
+    // Apply `reorder_columns` to the loaded plan
+    fn reorder_loaded_columns(&mut self) -> PolarsResult<()> {
+        let (Some(df), Some(first)) = (&mut self.df, &self.reorder_columns) else {
+            return Ok(());
+        };
+        let schema = df.collect_schema()?;
+        if let Some(missing) = first.iter().find(|c| !schema.contains(c.as_str())) {
+            polars_bail!(ColumnNotFound: "cannot reorder: column '{}' is not loaded", missing);
+        }
+        let order: Vec<String> = first
+            .iter()
+            .cloned()
+            .chain(
+                schema
+                    .iter_names()
+                    .map(|n| n.to_string())
+                    .filter(|n| !first.contains(n)),
+            )
+            .collect();
+
+        *df = df.clone().select(order.iter().map(|c| col(c.as_str())).collect::<Vec<_>>());
+        self.metrics.insert("columns_reordered".into(), first.len() as f64);
+        self.tags.insert("column_order".into(), order.join(","));
+        Ok(())
+    }
+
+    /// Check that the loaded plan resolves (every projected column exists) and that the
+    /// columns the pipeline does arithmetic on are numeric. Cheap: only the schema is
+    /// computed. Call after loading.
+    pub fn validate_schema(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = &mut self.df else {
+            polars_bail!(ComputeError: "nothing loaded to validate");
+        };
+        let schema = df.collect_schema()?;
+        let numeric = ["trip_distance", "passenger_count", "total_amount"]
+            .into_iter()
+            .chain(FARE_COMPONENTS)
+            .chain(["pickup_longitude", "pickup_latitude", "dropoff_longitude", "dropoff_latitude"]);
+        for name in numeric {
+            match schema.get(name) {
+                // optional: a file without one only skips the total_amount check
+                None if FARE_COMPONENTS.contains(&name) => {}
+                Some(dtype) if dtype.is_primitive_numeric() => {}
+                Some(dtype) => polars_bail!(SchemaMismatch: "column '{}' is {}, expected a number", name, dtype),
+                // left out on purpose
+                None if self.use_columns.is_some() => {}
+                None => polars_bail!(ColumnNotFound: "column '{}' is missing", name),
+            }
+        }
+        status!("✅ Schema OK ({} columns)", schema.len());
+        Ok(self)
+    }
+
+    /// Materialize only the configured hot columns of the current (cleaned) frame so
+    /// lookups on them skip the scan; the full plan stays lazy. Records `hot_columns_bytes`
+    /// and `hot_columns_time`. No-op when no hot columns are configured.
+    pub fn pin_hot_columns(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = &self.df else { return Ok(self) };
+        if self.hot_columns.is_empty() {
+            return Ok(self);
+        }
+        status!("Pinning hot columns: {}...", self.hot_columns.join(", "));
+        let start = Instant::now();
+
+        let hot = self
+            .optimized(df.clone().select(self.hot_columns.iter().map(|c| col(c.as_str())).collect::<Vec<_>>()))
+            .collect()?;
+
+        let t = start.elapsed().as_secs_f64();
+        let bytes = hot.estimated_size() as f64;
+        self.metrics.insert("hot_columns_time".into(), t);
+        self.metrics.insert("hot_columns_bytes".into(), bytes);
+        status!("✅ Pinned {} rows ({:.1} MB) in {:.2}s", hot.height(), bytes / 1_048_576.0, t);
+        self.hot = Some(hot);
+        bump_peak(&mut self.metrics, "after_pin");
         Ok(self)
     }
 
+    /// The frame pinned by `pin_hot_columns`, if any.
+    pub fn hot_frame(&self) -> Option<&DataFrame> {
+        self.hot.as_ref()
+    }
+
     pub fn clean_data(&mut self) -> PolarsResult<&mut Self> {
-        println!("Cleaning data...");
+        status!("Cleaning data...");
+        self.emit(ProgressEvent::StageStarted { stage: "clean" });
         let start = Instant::now();
 
+        let period = self.declared_period();
+        self.period = period;
+        if let Some(period) = period {
+            self.tags.insert("declared_period".into(), period.to_string());
+        }
         if let Some(df) = &self.df {
-            // permissive timestamp parse (handles fractional seconds)
-            let fmt: PlSmallStr = "%Y-%m-%d %H:%M:%S%.f".into();
-            let to_dt_opts = StrptimeOptions {
-                format: Some(fmt),
-                strict: false,   // coerce invalid instead of erroring
-                exact: false,
-                cache: true,
-                ..Default::default()
-            };
+            let schema = df.clone().collect_schema()?;
+            for name in ["tpep_pickup_datetime", "tpep_dropoff_datetime"] {
+                if schema.get(name).is_none() {
+                    polars_bail!(ColumnNotFound: "cleaning parses '{}'; add it to use_columns", name);
+                }
+            }
+            let policy = self.negative_duration_policy;
+            let (rules, skipped) = self.loaded_cleaning_rules(df)?;
+            if !skipped.is_empty() {
+                status!("⚠️  Cleaning rules skipped (columns not loaded): {}", skipped.join(", "));
+                self.tags.insert("skipped_cleaning_rules".into(), skipped.join(","));
+            }
+            let (pre_parse, parsed) = rules.split_at(PRE_PARSE_RULES);
 
             // Keep LAZY; cache once for reuse in later steps
-            let cleaned = df
-                .clone()
-                // push down cheap filters before parsing
-                .filter(
-                    col("pickup_longitude").neq(lit(0.0))
-                        .and(col("pickup_latitude").neq(lit(0.0)))
-                        .and(col("dropoff_longitude").neq(lit(0.0)))
-                        .and(col("dropoff_latitude").neq(lit(0.0)))
-                        .and(col("trip_distance").gt(lit(0.0)))
-                        .and(col("trip_distance").lt(lit(100.0)))
-                        .and(col("passenger_count").gt(lit(0)))
-                        .and(col("passenger_count").lt_eq(lit(6))),
-                )
-                .with_columns([
-                    col("tpep_pickup_datetime").str().strptime(
-                        DataType::Datetime(TimeUnit::Microseconds, None),
-                        to_dt_opts.clone(),
-                        lit("coerce"), // be tolerant
-                    ),
-                    col("tpep_dropoff_datetime").str().strptime(
-                        DataType::Datetime(TimeUnit::Microseconds, None),
-                        to_dt_opts,
-                        lit("coerce"),
-                    ),
-                ])
-                .with_columns([
-                    (col("tpep_dropoff_datetime") - col("tpep_pickup_datetime"))
-                        .dt()
-                        .total_minutes()
-                        .alias("trip_duration_minutes"),
-                ])
-                .filter(
-                    col("trip_duration_minutes")
-                        .gt(lit(0))
-                        .and(col("trip_duration_minutes").lt(lit(480))),
-                )
-                .cache();
+            let timed = with_trip_times(
+                df.clone()
+                    // push down cheap filters before parsing
+                    .filter(keep_all(pre_parse)),
+            )
+            .cache();
+
+            // Dropoff before pickup and pickups outside the period: counted whatever the policy
+            let mut counts = vec![col("trip_duration_minutes")
+                .lt(lit(0))
+                .sum()
+                .cast(DataType::Float64)
+                .alias("negative_duration_rows")];
+            if let Some(period) = period {
+                counts.push(period.contains().not().sum().cast(DataType::Float64).alias("out_of_period_rows"));
+            }
+            let counts = self.optimized(timed.clone().select(counts)).collect()?;
+            for column in counts.get_columns() {
+                let n = column.f64()?.get(0).unwrap_or(0.0);
+                self.metrics.insert(column.name().to_string(), n);
+            }
 
+            let mut cleaned = timed.filter(keep_all(parsed));
+            if policy == NegativeDurationPolicy::ClampZero {
+                cleaned = cleaned.with_columns([when(col("trip_duration_minutes").lt(lit(0)))
+                    .then(lit(0).cast(DataType::Int64))
+                    .otherwise(col("trip_duration_minutes"))
+                    .alias("trip_duration_minutes")]);
+            }
+            if let (Some(period), OutOfPeriodPolicy::Flag) = (period, self.out_of_period) {
+                cleaned = cleaned.with_columns([period.contains().not().alias("out_of_period")]);
+            }
+            let cleaned = cleaned.cache();
+
+            self.raw = Some(df.clone());
             self.df = Some(cleaned);
         }
 
         let t = start.elapsed().as_secs_f64();
         self.metrics.insert("clean_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "clean", seconds: t });
         bump_peak(&mut self.metrics, "after_clean");
-        println!("✅ Data cleaned in {:.2}s", t);
+        status!("✅ Data cleaned in {:.2}s", t);
         Ok(self)
     }
 
+    // Daily measures: the configured specs plus surcharge revenue when the input has it
+    fn daily_specs(&self) -> Vec<AggSpec> {
+        let mut specs = self.agg_specs.clone();
+        specs.extend(
+            self.surcharge_columns
+                .iter()
+                .map(|c| AggSpec::new(c, Agg::Sum).alias(&format!("total_{}", c))),
+        );
+        specs
+    }
+
+    // Daily table over a frame with a `date` column. Every spec goes into one agg([...])
+    // so the group_by runs as a single pass.
+    pub(crate) fn daily_plan(&self, feats: LazyFrame) -> LazyFrame {
+        feats
+            .group_by([col("date")])
+            .agg(self.daily_specs().iter().map(|s| self.agg_expr(s)).collect::<Vec<_>>())
+            .sort(["date"], Default::default())
+    }
+
+    // The cleaned frame in the configured units, with the pickup date, hour and weekday
+    fn features_plan(&self, df: LazyFrame) -> LazyFrame {
+        self.units.scale_distance(df).with_columns([
+            col("tpep_pickup_datetime").dt().date().alias("date"),
+            col("tpep_pickup_datetime").dt().hour().alias("hour"),
+            col("tpep_pickup_datetime").dt().weekday().alias("weekday"),
+        ])
+    }
+
+    fn hourly_plan(&self, feats: LazyFrame) -> LazyFrame {
+        feats
+            .group_by([col("hour")])
+            .agg([
+                col("trip_distance").count().alias("trip_count"),
+                col("trip_distance").mean().alias("avg_trip_distance"),
+                col("trip_duration_minutes").mean().alias("avg_trip_duration"),
+                self.agg_expr(&AggSpec::new("total_amount", Agg::Mean).alias("avg_total_amount")),
+            ])
+            .sort(["hour"], Default::default())
+    }
+
+    fn dow_plan(&self, feats: LazyFrame) -> LazyFrame {
+        feats
+            .group_by([col("weekday")])
+            .agg([
+                col("trip_distance").count().alias("trip_count"),
+                col("trip_distance").mean().alias("avg_trip_distance"),
+                self.agg_expr(&AggSpec::new("total_amount", Agg::Mean).alias("avg_total_amount")),
+            ])
+            .sort(["weekday"], Default::default())
+    }
+
+    fn night_plan(df: LazyFrame) -> LazyFrame {
+        df.with_columns([is_night().alias("is_night")])
+            .group_by([col("is_night")])
+            .agg([
+                col("trip_distance").count().cast(DataType::Int64).alias("trip_count"),
+                col("fare_amount").mean().alias("avg_fare"),
+                col("tip_amount").mean().alias("avg_tip"),
+            ])
+            .sort(["is_night"], Default::default())
+    }
+
+    /// The uncollected plan of result `name` (daily, hourly, dow or night) over the cleaned
+    /// frame; None for another name or before `clean_data`.
+    pub fn result_plan(&self, name: &str) -> Option<LazyFrame> {
+        let df = self.df.clone()?;
+        match name {
+            "daily" => Some(self.daily_plan(self.features_plan(df))),
+            "hourly" => Some(self.hourly_plan(self.features_plan(df))),
+            "dow" => Some(self.dow_plan(self.features_plan(df))),
+            "night" => Some(Self::night_plan(df)),
+            _ => None,
+        }
+    }
+
+    /// Collect only result `name` (see [`Self::result_plan`]) with the engine picked by
+    /// `with_streaming`, replacing any earlier copy in the results. On the streaming engine
+    /// the cleaned input goes through in morsels, so memory is bounded by the group state and
+    /// the result rather than the input. Records `result_collect_time` and reports it as the
+    /// aggregate stage; false when there is no such result.
+    pub fn collect_result(&mut self, name: &str) -> PolarsResult<bool> {
+        let Some(plan) = self.result_plan(name) else {
+            return Ok(false);
+        };
+        self.emit(ProgressEvent::StageStarted { stage: "aggregate" });
+        let start = Instant::now();
+        let df = self.collect_plan(plan)?;
+        let stem = match name {
+            "daily" => "polars_daily_stats",
+            "hourly" => "polars_hourly_stats",
+            "dow" => "polars_dow_stats",
+            _ => "polars_night_stats",
+        };
+        self.results.retain(|(s, _)| *s != stem);
+        self.results.push((stem, df));
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("result_collect_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "aggregate", seconds: t });
+        bump_peak(&mut self.metrics, "after_result_collect");
+        Ok(true)
+    }
+
     pub fn aggregate_data(&mut self) -> PolarsResult<&mut Self> {
-        println!("Performing aggregations...");
+        status!("Performing aggregations...");
+        self.emit(ProgressEvent::StageStarted { stage: "aggregate" });
         let start = Instant::now();
 
         if let Some(df) = &self.df {
             // Stay LAZY and collect once per aggregation branch (clean plan is cached)
-            let df_feats = df.clone().with_columns([
-                col("tpep_pickup_datetime").dt().date().alias("date"),
-                col("tpep_pickup_datetime").dt().hour().alias("hour"),
-                col("tpep_pickup_datetime").dt().weekday().alias("weekday"),
-            ]);
+            let df_feats = self.features_plan(df.clone());
 
-            let _daily = df_feats
-                .clone()
-                .group_by([col("date")])
-                .agg([
-                    col("trip_distance").count().alias("trip_count"),
-                    col("trip_distance").mean().alias("avg_trip_distance"),
-                    col("trip_distance").sum().alias("total_trip_distance"),
-                    col("trip_duration_minutes").mean().alias("avg_trip_duration"),
-                    col("trip_duration_minutes").sum().alias("total_trip_duration"),
-                    col("passenger_count").sum().alias("total_passengers"),
-                    col("total_amount").mean().alias("avg_total_amount"),
-                    col("total_amount").sum().alias("total_revenue"),
-                ])
-                .collect()?; // materialize this branch
+            let daily_plan = self.daily_plan(df_feats.clone());
+            self.metrics.insert("aggregations_computed".into(), self.daily_specs().len() as f64);
+            self.metrics.insert("aggregation_scans".into(), count_scans(&daily_plan)? as f64);
+            let daily = self.collect_plan(daily_plan)?; // materialize this branch
 
-            let _hourly = df_feats
-                .clone()
-                .group_by([col("hour")])
-                .agg([
-                    col("trip_distance").count().alias("trip_count"),
-                    col("trip_distance").mean().alias("avg_trip_distance"),
-                    col("trip_duration_minutes").mean().alias("avg_trip_duration"),
-                    col("total_amount").mean().alias("avg_total_amount"),
-                ])
-                .collect()?;
+            // A metric holds one number, so it gets the overall mode; the per-zone modes go in
+            // their own result, when the file has zones (2015 files only have coordinates)
+            let schema = df_feats.clone().collect_schema()?;
+            let mut zone_payment = None;
+            if schema.get("payment_type").is_some() {
+                let mode = AggSpec::new("payment_type", Agg::Mode).alias("most_common_payment_type");
+                let payment = self.optimized(df_feats.clone().select([mode.to_expr()])).collect()?;
+                if let Some(value) = payment.get_columns()[0].cast(&DataType::Float64)?.f64()?.get(0) {
+                    self.metrics.insert("most_common_payment_type".into(), value);
+                }
+                if schema.get("PULocationID").is_some() {
+                    let zone_plan = df_feats
+                        .clone()
+                        .group_by([col("PULocationID")])
+                        .agg([mode.to_expr(), col("payment_type").count().alias("trip_count")])
+                        .sort(["PULocationID"], Default::default());
+                    zone_payment = Some(self.collect_plan(zone_plan)?);
+                }
+            } else {
+                self.metrics.remove("most_common_payment_type");
+            }
 
-            let _dow = df_feats
-                .clone()
-                .group_by([col("weekday")])
-                .agg([
-                    col("trip_distance").count().alias("trip_count"),
-                    col("trip_distance").mean().alias("avg_trip_distance"),
-                    col("total_amount").mean().alias("avg_total_amount"),
-                ])
-                .collect()?;
+            let hourly = self.collect_plan(self.hourly_plan(df_feats.clone()))?;
+            let dow = self.collect_plan(self.dow_plan(df_feats))?;
 
-            // (If you want to persist these, do it here; we only time the step.)
-            let _ = (_daily, _hourly, _dow);
+            // Kept for save_results, which writes them next to the metrics
+            self.results = vec![
+                ("polars_daily_stats", daily),
+                ("polars_hourly_stats", hourly),
+                ("polars_dow_stats", dow),
+            ];
+            if let Some(zone_payment) = zone_payment {
+                self.results.push(("polars_zone_payment_stats", zone_payment));
+            }
         }
 
         let t = start.elapsed().as_secs_f64();
         self.metrics.insert("aggregate_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "aggregate", seconds: t });
         bump_peak(&mut self.metrics, "after_aggregate");
-        println!("✅ Aggregations done in {:.2}s", t);
+        status!("✅ Aggregations done in {:.2}s", t);
+        Ok(self)
+    }
+
+    /// Append `surge_indicator`: the trip's fare per minute over the median fare per minute of
+    /// its pickup hour (a window expression). Minutes are fractional, from the timestamps, so
+    /// short trips don't divide by a truncated 0; trips without a positive duration and hours
+    /// whose median is 0 get null. Records `surge_trips`, the trips at or above
+    /// `SURGE_THRESHOLD`, and `surge_time`. Call after `clean_data`.
+    pub fn add_surge_indicator(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else { return Ok(self) };
+        status!("Computing surge indicator...");
+        let start = Instant::now();
+
+        // trip_duration_minutes is whole (and possibly clamped or negative) minutes
+        let minutes = (col("tpep_dropoff_datetime") - col("tpep_pickup_datetime"))
+            .dt()
+            .total_seconds()
+            .cast(DataType::Float64)
+            / lit(60.0);
+        let null = || lit(Null {}).cast(DataType::Float64);
+        let fare_per_minute = when(minutes.clone().gt(lit(0.0)))
+            .then(col("fare_amount").cast(DataType::Float64) / minutes)
+            .otherwise(null());
+        let hour_median = fare_per_minute
+            .clone()
+            .median()
+            .over([col("tpep_pickup_datetime").dt().hour()]);
+        let surge = when(hour_median.clone().gt(lit(0.0)))
+            .then(fare_per_minute / hour_median)
+            .otherwise(null());
+        let with_surge = df.with_columns([surge.alias("surge_indicator")]).cache();
+
+        let surge_trips = self
+            .optimized(with_surge.clone().select([col("surge_indicator")
+                .gt_eq(lit(SURGE_THRESHOLD))
+                .cast(DataType::Int64)
+                .sum()
+                .alias("surge_trips")]))
+            .collect()?;
+        let n = surge_trips.column("surge_trips")?.i64()?.get(0).unwrap_or(0);
+        self.df = Some(with_surge);
+
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("surge_trips".into(), n as f64);
+        self.metrics.insert("surge_time".into(), t);
+        status!("✅ {} surge trips (≥{}x hourly median fare/min) in {:.2}s", n, SURGE_THRESHOLD, t);
+        Ok(self)
+    }
+
+    /// p50/p90/p99 of `total_amount`, `trip_distance` and `trip_duration_minutes`, recorded as
+    /// `<column>_<pXX>` with `quantile_time`. With `approximate_quantiles` and more rows than
+    /// `quantile_sample_rows`, they come from a seeded random sample of that many rows, drawn
+    /// in the same pass that counts the rows. `quantile_method` (`exact` or
+    /// `sampled`) is recorded, along with `quantile_rank_error_pct`, the 95% bound on how many
+    /// percentile points the sample can be off (0 when exact). Call after `clean_data`.
+    pub fn compute_quantiles(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else { return Ok(self) };
+        status!("Computing quantiles...");
+        let start = Instant::now();
+
+        // One pass: the row count and every quantile, from a seeded random sample of each
+        // column when approximating (all of it when the frame is no larger than the sample)
+        let limit = self.quantile_sample_rows as IdxSize;
+        let sample_rows = when(len().gt(lit(limit))).then(lit(limit)).otherwise(len());
+        let mut exprs = vec![len().cast(DataType::Int64).alias("quantile_input_rows")];
+        for c in QUANTILE_COLUMNS {
+            let mut values = col(c).cast(DataType::Float64);
+            if self.approximate_quantiles {
+                values = values.sample_n(sample_rows.clone(), false, false, Some(QUANTILE_SAMPLE_SEED));
+            }
+            exprs.extend(QUANTILES.iter().map(|(q, name)| {
+                values.clone().quantile(lit(*q), QuantileMethod::Linear).alias(format!("{}_{}", c, name))
+            }));
+        }
+        let out = self.optimized(df.select(exprs)).collect()?;
+        let rows = out.column("quantile_input_rows")?.i64()?.get(0).unwrap_or(0).max(0) as usize;
+        for column in out.get_columns().iter().skip(1) {
+            if let Some(v) = column.f64()?.get(0) {
+                self.metrics.insert(column.name().to_string(), v);
+            }
+        }
+        let sampled = self.approximate_quantiles && rows > self.quantile_sample_rows;
+        let used = if sampled { self.quantile_sample_rows } else { rows };
+
+        // Rank error of a quantile from a simple random sample of n out of N rows (95%):
+        // 1.96 * sqrt(p(1-p)/n * (1 - n/N)), largest at p50
+        let rank_error = if sampled && used > 0 {
+            let correction = 1.0 - used as f64 / rows as f64;
+            QUANTILES
+                .iter()
+                .map(|(q, _)| 1.96 * (q * (1.0 - q) / used as f64 * correction).sqrt() * 100.0)
+                .fold(0.0, f64::max)
+        } else {
+            0.0
+        };
+        let method = if sampled { "sampled" } else { "exact" };
+        self.tags.insert("quantile_method".into(), method.into());
+        self.metrics.insert("quantile_rows".into(), used as f64);
+        self.metrics.insert("quantile_rank_error_pct".into(), rank_error);
+
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("quantile_time".into(), t);
+        status!(
+            "✅ Quantiles ({}, {} of {} rows, ±{:.2} percentile points) in {:.2}s",
+            method,
+            used,
+            rows,
+            rank_error,
+            t
+        );
+        Ok(self)
+    }
+
+    /// Trip count, average fare and average tip for night (`is_night`: pickup 22:00–06:00)
+    /// vs day trips, saved as `polars_night_stats`. Records `night_trip_count`,
+    /// `night_avg_fare`, `night_avg_tip` and the `day_*` counterparts. Call after `clean_data`.
+    pub fn aggregate_by_night(&mut self) -> PolarsResult<&mut Self> {
+        let Some(df) = &self.df else { return Ok(self) };
+        status!("Aggregating night vs day trips...");
+        let start = Instant::now();
+
+        let stats = self.optimized(Self::night_plan(df.clone())).collect()?;
+
+        let is_night = stats.column("is_night")?.bool()?.clone();
+        let count = stats.column("trip_count")?.i64()?.clone();
+        let fare = stats.column("avg_fare")?.f64()?.clone();
+        let tip = stats.column("avg_tip")?.f64()?.clone();
+        for i in 0..stats.height() {
+            let prefix = if is_night.get(i).unwrap_or(false) { "night" } else { "day" };
+            self.metrics.insert(format!("{}_trip_count", prefix), count.get(i).unwrap_or(0) as f64);
+            if let Some(v) = fare.get(i) {
+                self.metrics.insert(format!("{}_avg_fare", prefix), v);
+            }
+            if let Some(v) = tip.get(i) {
+                self.metrics.insert(format!("{}_avg_tip", prefix), v);
+            }
+        }
+        self.results.push(("polars_night_stats", stats));
+
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("night_split_time".into(), t);
+        status!(
+            "✅ Night vs day in {:.2}s (avg tip {:.2} vs {:.2})",
+            t,
+            self.metrics.get("night_avg_tip").copied().unwrap_or(0.0),
+            self.metrics.get("day_avg_tip").copied().unwrap_or(0.0)
+        );
         Ok(self)
     }
 
     pub fn sort_and_filter(&mut self) -> PolarsResult<&mut Self> {
-        println!("Sorting and filtering...");
+        status!("Sorting and filtering...");
+        self.emit(ProgressEvent::StageStarted { stage: "sort_filter" });
         let start = Instant::now();
 
         if let Some(df) = &self.df {
+            // total_amount should equal the sum of its components (incl. any surcharges); only
+            // checked when every component was loaded
+            let schema = df.clone().collect_schema()?;
+            let components: Vec<&str> =
+                FARE_COMPONENTS.iter().chain(self.surcharge_columns.iter()).copied().collect();
+            let reconcile =
+                schema.get("total_amount").is_some() && components.iter().all(|c| schema.get(c).is_some());
+            let drift = col("total_amount")
+                - components
+                    .iter()
+                    .map(|c| col(*c).fill_null(lit(0.0)))
+                    .reduce(|acc, c| acc + c)
+                    .unwrap_or_else(|| lit(0.0));
+
             // No full-data sort (very expensive). We compute the requested counts in one pass.
-            let counts = df
+            let mut count_exprs = vec![
+                // ensure Int64 before sum to avoid u32/i64 mismatches
+                col("trip_distance").count()
+                    .cast(DataType::Int64)
+                    .alias("rows_after_cleaning"),
+                long_trip()
+                    .cast(DataType::Int64).sum()
+                    .alias("long_trips_count"),
+                expensive_trip()
+                    .cast(DataType::Int64).sum()
+                    .alias("expensive_trips_count"),
+                (col("hour").eq(lit(7))
+                    .or(col("hour").eq(lit(8)))
+                    .or(col("hour").eq(lit(9)))
+                    .or(col("hour").eq(lit(17)))
+                    .or(col("hour").eq(lit(18)))
+                    .or(col("hour").eq(lit(19))))
+                    .cast(DataType::Int64).sum()
+                    .alias("rush_hour_trips_count"),
+                col("weekday").gt_eq(lit(6))
+                    .cast(DataType::Int64).sum()
+                    .alias("weekend_trips_count"),
+                (col("trip_distance").gt(lit(5.0))
+                    .and(col("total_amount").gt(lit(30.0)))
+                    .and(col("passenger_count").gt_eq(lit(2))))
+                    .cast(DataType::Int64).sum()
+                    .alias("premium_trips_count"),
+            ];
+            if reconcile {
+                count_exprs.push(
+                    (drift.clone().gt(lit(TOTAL_AMOUNT_TOLERANCE))
+                        .or(drift.lt(lit(-TOTAL_AMOUNT_TOLERANCE))))
+                        .cast(DataType::Int64).sum()
+                        .alias("total_amount_mismatch_count"),
+                );
+            }
+            let counts_plan = df
                 .clone()
                 .with_columns([
                     col("tpep_pickup_datetime").dt().hour().alias("hour"),
                     col("tpep_pickup_datetime").dt().weekday().alias("weekday"),
                 ])
-                .select([
-                    // ensure Int64 before sum to avoid u32/i64 mismatches
-                    col("trip_distance").count()
-                        .cast(DataType::Int64)
-                        .alias("rows_after_cleaning"),
-                    col("trip_distance").gt(lit(10.0))
-                        .cast(DataType::Int64).sum()
-                        .alias("long_trips_count"),
-                    col("total_amount").gt(lit(50.0))
-                        .cast(DataType::Int64).sum()
-                        .alias("expensive_trips_count"),
-                    (col("hour").eq(lit(7))
-                        .or(col("hour").eq(lit(8)))
-                        .or(col("hour").eq(lit(9)))
-                        .or(col("hour").eq(lit(17)))
-                        .or(col("hour").eq(lit(18)))
-                        .or(col("hour").eq(lit(19))))
-                        .cast(DataType::Int64).sum()
-                        .alias("rush_hour_trips_count"),
-                    col("weekday").gt_eq(lit(6))
-                        .cast(DataType::Int64).sum()
-                        .alias("weekend_trips_count"),
-                    (col("trip_distance").gt(lit(5.0))
-                        .and(col("total_amount").gt(lit(30.0)))
-                        .and(col("passenger_count").gt_eq(lit(2))))
-                        .cast(DataType::Int64).sum()
-                        .alias("premium_trips_count"),
-                ])
-                .collect()?;
+                .select(count_exprs);
+            let counts = self.collect_plan(counts_plan)?;
 
             let get_i64 = |name: &str| -> PolarsResult<i64> {
                 Ok(counts.column(name)?.i64()?.get(0).unwrap_or(0))
@@ -242,8 +2258,13 @@ impl PolarsETL {
             self.metrics.insert("rush_hour_trips_count".into(), get_i64("rush_hour_trips_count")? as f64);
             self.metrics.insert("weekend_trips_count".into(),   get_i64("weekend_trips_count")? as f64);
             self.metrics.insert("premium_trips_count".into(),   get_i64("premium_trips_count")? as f64);
+            if reconcile {
+                self.metrics.insert("total_amount_mismatch_count".into(), get_i64("total_amount_mismatch_count")? as f64);
+            } else {
+                self.metrics.remove("total_amount_mismatch_count");
+            }
 
-            println!(
+            status!(
                 "Found {} long trips, {} expensive trips",
                 self.metrics["long_trips_count"] as usize,
                 self.metrics["expensive_trips_count"] as usize
@@ -252,27 +2273,521 @@ impl PolarsETL {
 
         let t = start.elapsed().as_secs_f64();
         self.metrics.insert("sort_filter_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "sort_filter", seconds: t });
         bump_peak(&mut self.metrics, "after_sort_filter");
-        println!("✅ Sort & filter in {:.2}s", t);
+        status!("✅ Sort & filter in {:.2}s", t);
         Ok(self)
     }
 
     pub fn save_results(&mut self, output_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Saving results...");
+        self.save_results_as(output_dir, OutputFormat::Csv)
+    }
+
+    /// Like [`save_results`](Self::save_results) but writes the aggregated frames in `format`.
+    /// Fails with [`EtlError::FormatNotSupported`] before touching disk if the format
+    /// was not compiled in.
+    pub fn save_results_as(
+        &mut self,
+        output_dir: &str,
+        format: OutputFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        format.ensure_supported()?;
+        status!("Saving results...");
+        self.emit(ProgressEvent::StageStarted { stage: "save" });
         let start = Instant::now();
 
         std::fs::create_dir_all(output_dir)?;
-        let metrics_json = serde_json::to_string_pretty(&self.metrics)?;
+        for (stem, df) in self.results.iter_mut() {
+            let path = format!("{}/{}.{}", output_dir, stem, format.extension());
+            write_frame(df, &path, format)?;
+        }
+        if self.write_trip_sets {
+            self.save_trip_sets(output_dir)?;
+        }
+        let metrics_json = serde_json::to_string_pretty(&self.metrics_json())?;
         std::fs::write(format!("{}/polars_metrics.json", output_dir), metrics_json)?;
 
         let t = start.elapsed().as_secs_f64();
         self.metrics.insert("save_time".into(), t);
+        self.emit(ProgressEvent::StageFinished { stage: "save", seconds: t });
         bump_peak(&mut self.metrics, "after_save");
-        println!("✅ Results saved in {:.2}s", t);
+        status!("✅ Results saved in {:.2}s", t);
+        Ok(())
+    }
+
+    // Matching rows of each trip set as Parquet; records `<set>_rows` and the `<set>_path` tag
+    fn save_trip_sets(&mut self, output_dir: &str) -> Result<(), EtlError> {
+        OutputFormat::Parquet.ensure_supported()?;
+        let Some(df) = &self.df else { return Ok(()) };
+        for (name, predicate) in trip_sets() {
+            let mut rows = self.optimized(self.units.scale_distance(df.clone().filter(predicate))).collect()?;
+            let path = format!("{}/{}.{}", output_dir, name, OutputFormat::Parquet.extension());
+            write_frame(&mut rows, &path, OutputFormat::Parquet)?;
+            status!("  {} rows → {}", rows.height(), path);
+            self.metrics.insert(format!("{}_rows", name), rows.height() as f64);
+            self.tags.insert(format!("{}_path", name), path);
+        }
+        Ok(())
+    }
+
+    /// One aggregated result (`daily`, `hourly`, `dow`, or `night` after `aggregate_by_night`)
+    /// serialized as an Arrow IPC stream; None when that result wasn't computed.
+    pub fn result_ipc_stream(&mut self, name: &str) -> Result<Option<Vec<u8>>, EtlError> {
+        OutputFormat::Arrow.ensure_supported()?;
+        let stem = format!("polars_{}_stats", name);
+        let Some((_, df)) = self.results.iter_mut().find(|(s, _)| *s == stem) else {
+            return Ok(None);
+        };
+        #[cfg(feature = "ipc")]
+        {
+            let mut buf = Vec::new();
+            IpcStreamWriter::new(&mut buf).finish(df)?;
+            Ok(Some(buf))
+        }
+        #[cfg(not(feature = "ipc"))]
+        {
+            let _ = df;
+            unreachable!("checked by ensure_supported")
+        }
+    }
+
+    /// Hand result `name` (daily, hourly, dow or night, see [`Self::collect_result`]) to `sink`
+    /// as NDJSON, one object per row and `rows_per_chunk` rows per call, so the caller can
+    /// forward each chunk as it is encoded instead of holding the whole encoded table. Stops
+    /// early once `sink` returns false (e.g. the reader went away). Returns the rows handed
+    /// over, also recorded as `result_rows_streamed`; None if that result was not computed.
+    pub fn result_ndjson_chunks(
+        &mut self,
+        name: &str,
+        rows_per_chunk: usize,
+        mut sink: impl FnMut(Vec<u8>) -> bool,
+    ) -> PolarsResult<Option<usize>> {
+        let stem = format!("polars_{}_stats", name);
+        let Some((_, df)) = self.results.iter().find(|(s, _)| *s == stem) else {
+            return Ok(None);
+        };
+
+        let rows_per_chunk = rows_per_chunk.max(1);
+        let mut streamed = 0;
+        for offset in (0..df.height()).step_by(rows_per_chunk) {
+            let chunk = df.slice(offset as i64, rows_per_chunk);
+            let mut buf = Vec::new();
+            for row in 0..chunk.height() {
+                let mut obj = serde_json::Map::new();
+                for column in chunk.get_columns() {
+                    obj.insert(column.name().to_string(), json_value(column.get(row)?));
+                }
+                serde_json::to_writer(&mut buf, &obj).map_err(|e| polars_err!(ComputeError: "{}", e))?;
+                buf.push(b'\n');
+            }
+            if !sink(buf) {
+                break;
+            }
+            streamed += chunk.height();
+        }
+        self.metrics.insert("result_rows_streamed".into(), streamed as f64);
+        Ok(Some(streamed))
+    }
+
+    /// Write the run's data-quality report to `path` as JSON: input rows, rows dropped by
+    /// each cleaning rule (in order, with the reason), final rows and the retention
+    /// percentage. Counts come from one extra pass over the loaded data. Call after `clean_data`.
+    pub fn write_audit(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(raw) = &self.raw else {
+            return Err("no cleaned data to audit; run the clean stage first".into());
+        };
+
+        // Survivors after each cumulative rule, counted in a single pass
+        let mut keep: Option<Expr> = None;
+        let mut counts = vec![len().cast(DataType::Int64).alias("input_rows")];
+        let (applied, _) = self.loaded_cleaning_rules(raw)?;
+        for (name, _, rule) in applied.iter().cloned() {
+            let cumulative = match keep.take() {
+                Some(prev) => prev.and(rule),
+                None => rule,
+            };
+            keep = Some(cumulative.clone());
+            counts.push(cumulative.cast(DataType::Int64).sum().alias(name));
+        }
+        let counts = self.optimized(with_trip_times(raw.clone()).select(counts)).collect()?;
+        let get = |name: &str| -> PolarsResult<i64> { Ok(counts.column(name)?.i64()?.get(0).unwrap_or(0)) };
+
+        let input_rows = get("input_rows")?;
+        let mut remaining = input_rows;
+        let mut rules = Vec::new();
+        for (name, reason, _) in applied {
+            let after = get(name)?;
+            rules.push(serde_json::json!({
+                "rule": name,
+                "reason": reason,
+                "dropped": remaining - after,
+                "remaining": after,
+            }));
+            remaining = after;
+        }
+        let retention_pct = if input_rows > 0 { remaining as f64 / input_rows as f64 * 100.0 } else { 0.0 };
+
+        let audit = serde_json::json!({
+            "input": self.input_path,
+            "input_fingerprint": self.tags.get("input_fingerprint"),
+            "sample_size": self.sample_size,
+            "input_rows": input_rows,
+            "rules": rules,
+            "final_rows": remaining,
+            "retention_pct": retention_pct,
+        });
+        std::fs::write(path, serde_json::to_string_pretty(&audit)?)?;
+        status!(
+            "🧾 Audit written to {} ({} of {} rows kept, {:.1}%)",
+            path,
+            remaining,
+            input_rows,
+            retention_pct
+        );
         Ok(())
     }
 
     pub fn get_metrics(&self) -> &HashMap<String, f64> {
         &self.metrics
     }
+
+    pub fn get_tags(&self) -> &HashMap<String, String> {
+        &self.tags
+    }
+
+    /// Stage timings as a Markdown table (stage | time | rows/sec) for pasting into issues.
+    /// Throughput is based on `rows_after_cleaning` and left blank when the run didn't count
+    /// rows; skipped stages are omitted.
+    pub fn summary_markdown(&self) -> String {
+        let rows = self.metrics.get("rows_after_cleaning").copied();
+        let rate = |secs: f64| match rows {
+            Some(rows) if secs > 0.0 => format!("{:.0}", rows / secs),
+            _ => "–".to_string(),
+        };
+
+        // Hardware-normalized column only when the run recorded a machine_score
+        let scored = self.normalized(1.0).is_some();
+        let normalized = |secs: f64| match self.normalized(secs) {
+            Some(n) => format!(" {:.1} |", n),
+            None => String::new(),
+        };
+
+        let mut out = if scored {
+            String::from("| stage | time (s) | ÷ machine_score | rows/sec |\n|---|---:|---:|---:|\n")
+        } else {
+            String::from("| stage | time (s) | rows/sec |\n|---|---:|---:|\n")
+        };
+        let mut total = 0.0;
+        for stage in Stage::ALL {
+            if let Some(&secs) = self.metrics.get(&format!("{}_time", stage.name())) {
+                total += secs;
+                out.push_str(&format!("| {} | {:.3} |{} {} |\n", stage.name(), secs, normalized(secs), rate(secs)));
+            }
+        }
+        out.push_str(&format!(
+            "| **total** | **{:.3}** |{} **{}** |\n",
+            total,
+            normalized(total),
+            rate(total)
+        ));
+        out
+    }
+
+    /// The first `rows` rows of each aggregated result, for the console. Date and datetime
+    /// columns are rendered with the strftime `date_format` (None = ISO 8601); only the
+    /// preview is formatted, the saved results keep their types.
+    pub fn preview(&self, rows: usize, date_format: Option<&str>) -> PolarsResult<String> {
+        let mut out = String::new();
+        for (stem, df) in &self.results {
+            let columns: Vec<Expr> = df
+                .schema()
+                .iter()
+                .map(|(name, dtype)| {
+                    let format = match dtype {
+                        DataType::Date => Some(date_format.unwrap_or("%Y-%m-%d")),
+                        DataType::Datetime(_, _) => Some(date_format.unwrap_or("%Y-%m-%dT%H:%M:%S")),
+                        _ => None,
+                    };
+                    match format {
+                        Some(format) => col(name.clone()).dt().to_string(format),
+                        None => col(name.clone()),
+                    }
+                })
+                .collect();
+            let head = df.head(Some(rows)).lazy().select(columns).collect()?;
+            out.push_str(&format!("{}\n{}\n", stem, head));
+        }
+        Ok(out)
+    }
+
+    /// Numeric metrics and tags merged into the flat object written as `polars_metrics.json`.
+    pub fn metrics_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        for (k, v) in &self.metrics {
+            obj.insert(k.clone(), serde_json::json!(v));
+        }
+        for (k, v) in &self.tags {
+            obj.insert(k.clone(), serde_json::json!(v));
+        }
+        serde_json::Value::Object(obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coerce_counts_failed_casts_but_not_nulls() {
+        let raw = df!("passenger_count" => [Some("1"), Some("x"), Some("3"), None]).unwrap();
+        let mut etl = PolarsETL::new()
+            .with_coerce_types(HashMap::from([("passenger_count".to_string(), DataType::Int64)]));
+        etl.df = Some(raw.lazy());
+        etl.coerce_columns().unwrap();
+
+        assert_eq!(etl.metrics["coerce_failures_passenger_count"], 1.0);
+        let coerced = etl.df.clone().unwrap().collect().unwrap();
+        let column = coerced.column("passenger_count").unwrap();
+        assert_eq!(column.dtype(), &DataType::Int64);
+        assert_eq!(column.null_count(), 2); // the original null and the "x"
+    }
+
+    // An overnight trip, a dropoff recorded before its pickup and an ordinary daytime trip
+    fn midnight_trips() -> DataFrame {
+        df!(
+            "tpep_pickup_datetime" => ["2015-01-01 23:50:00", "2015-01-01 12:30:00", "2015-01-01 08:00:00"],
+            "tpep_dropoff_datetime" => ["2015-01-02 00:10:00", "2015-01-01 12:10:00", "2015-01-01 08:15:00"],
+        )
+        .unwrap()
+    }
+
+    // Pickup time → trip duration of the rows left after cleaning with `policy`
+    fn cleaned_durations(policy: NegativeDurationPolicy) -> (PolarsETL, Vec<(String, i64)>) {
+        let mut etl = PolarsETL::new().with_negative_duration_policy(policy);
+        etl.df = Some(midnight_trips().lazy());
+        etl.clean_data().unwrap();
+        let cleaned = etl.df.clone().unwrap().collect().unwrap();
+        let pickups = cleaned.column("tpep_pickup_datetime").unwrap().cast(&DataType::String).unwrap();
+        let durations = cleaned.column("trip_duration_minutes").unwrap().cast(&DataType::Int64).unwrap();
+        let rows = pickups
+            .str()
+            .unwrap()
+            .into_iter()
+            .zip(durations.i64().unwrap())
+            .map(|(pickup, minutes)| (pickup.unwrap()[11..16].to_string(), minutes.unwrap()))
+            .collect();
+        (etl, rows)
+    }
+
+    #[test]
+    fn overnight_trips_survive_every_negative_duration_policy() {
+        for policy in [NegativeDurationPolicy::Drop, NegativeDurationPolicy::ClampZero, NegativeDurationPolicy::Keep] {
+            let (etl, rows) = cleaned_durations(policy);
+            assert_eq!(etl.metrics["negative_duration_rows"], 1.0, "{:?}", policy);
+            assert!(rows.contains(&("23:50".to_string(), 20)), "{:?}: {:?}", policy, rows);
+            assert!(rows.contains(&("08:00".to_string(), 15)), "{:?}: {:?}", policy, rows);
+        }
+    }
+
+    #[test]
+    fn negative_durations_are_dropped_clamped_or_kept() {
+        let negative = |policy| cleaned_durations(policy).1.into_iter().find(|(pickup, _)| pickup == "12:30");
+        assert_eq!(negative(NegativeDurationPolicy::Drop), None);
+        assert_eq!(negative(NegativeDurationPolicy::ClampZero), Some(("12:30".to_string(), 0)));
+        assert_eq!(negative(NegativeDurationPolicy::Keep), Some(("12:30".to_string(), -20)));
+    }
+
+    fn period(start: (i32, u32, u32), end: (i32, u32, u32)) -> DeclaredPeriod {
+        let date = |(y, m, d)| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        DeclaredPeriod { start: date(start), end: date(end) }
+    }
+
+    #[test]
+    fn declared_period_from_file_name() {
+        let january = period((2015, 1, 1), (2015, 2, 1));
+        assert_eq!(DeclaredPeriod::from_file_name("../data/yellow_tripdata_2015-01.csv"), Some(january));
+        assert_eq!(
+            DeclaredPeriod::from_file_name("yellow_tripdata_2015-12.parquet"),
+            Some(period((2015, 12, 1), (2016, 1, 1)))
+        );
+        // The month comes from the file name, not the directories
+        assert_eq!(DeclaredPeriod::from_file_name("2019-06/trips.csv"), None);
+        assert_eq!(DeclaredPeriod::from_file_name("yellow_tripdata_2015-13.csv"), None);
+    }
+
+    #[test]
+    fn declared_period_from_str() {
+        assert_eq!("2015-03".parse(), Ok(period((2015, 3, 1), (2015, 4, 1))));
+        assert_eq!("2015-12".parse(), Ok(period((2015, 12, 1), (2016, 1, 1))));
+        assert_eq!("2015-01-10..2015-01-20".parse(), Ok(period((2015, 1, 10), (2015, 1, 20))));
+        for bad in ["2015", "2015-13", "2015-01-20..2015-01-10", "2015-01-10..2015-01-10", "jan..feb", ""] {
+            assert!(bad.parse::<DeclaredPeriod>().is_err(), "{:?} parsed", bad);
+        }
+    }
+
+    // Two January 2015 trips and two with clock-error pickups years away
+    fn trips_outside_january() -> DataFrame {
+        df!(
+            "tpep_pickup_datetime" => ["2015-01-05 10:00:00", "2001-01-01 00:00:00", "2015-01-20 18:00:00", "2088-01-01 00:00:00"],
+            "tpep_dropoff_datetime" => ["2015-01-05 10:10:00", "2001-01-01 00:10:00", "2015-01-20 18:10:00", "2088-01-01 00:10:00"],
+        )
+        .unwrap()
+    }
+
+    fn clean_with_period(policy: OutOfPeriodPolicy) -> (PolarsETL, DataFrame) {
+        let mut etl = PolarsETL::new()
+            .with_declared_period(PeriodCheck::Fixed("2015-01".parse().unwrap()), 1)
+            .with_out_of_period(policy);
+        etl.df = Some(trips_outside_january().lazy());
+        etl.clean_data().unwrap();
+        let cleaned = etl.df.clone().unwrap().collect().unwrap();
+        (etl, cleaned)
+    }
+
+    #[test]
+    fn out_of_period_pickups_are_dropped() {
+        let (etl, cleaned) = clean_with_period(OutOfPeriodPolicy::Drop);
+        assert_eq!(etl.metrics["out_of_period_rows"], 2.0);
+        assert_eq!(cleaned.height(), 2);
+        assert!(cleaned.column("out_of_period").is_err());
+    }
+
+    #[test]
+    fn out_of_period_pickups_are_flagged() {
+        let (etl, cleaned) = clean_with_period(OutOfPeriodPolicy::Flag);
+        assert_eq!(etl.metrics["out_of_period_rows"], 2.0);
+        assert_eq!(cleaned.height(), 4);
+        let flagged = cleaned.column("out_of_period").unwrap().bool().unwrap().sum();
+        assert_eq!(flagged, Some(2));
+    }
+
+    fn projection_for(use_columns: Option<Vec<String>>) -> (PolarsETL, Vec<Expr>) {
+        let schema = Schema::from_iter([
+            Field::new("tpep_pickup_datetime".into(), DataType::String),
+            Field::new("tpep_dropoff_datetime".into(), DataType::String),
+            Field::new("fare_amount".into(), DataType::Float64),
+            Field::new("congestion_surcharge".into(), DataType::Float64),
+        ]);
+        let mut etl = PolarsETL::new().with_use_columns(use_columns);
+        let columns = etl.projection(&schema).unwrap();
+        (etl, columns)
+    }
+
+    #[test]
+    fn surcharges_follow_use_columns() {
+        let (etl, _) = projection_for(None);
+        assert_eq!(etl.surcharge_columns, vec!["congestion_surcharge"]);
+
+        let wanted = ["tpep_pickup_datetime", "fare_amount"].map(String::from).to_vec();
+        let (etl, columns) = projection_for(Some(wanted));
+        assert!(etl.surcharge_columns.is_empty());
+        assert_eq!(etl.metrics["has_surcharge_columns"], 0.0);
+        assert!(etl.daily_specs().iter().all(|spec| !spec.column.contains("surcharge")));
+        assert_eq!(columns.len(), 2);
+    }
+
+    #[test]
+    fn collect_result_computes_only_the_requested_table() {
+        let trips = df!(
+            "tpep_pickup_datetime" => ["2015-01-01 08:00:00", "2015-01-01 08:30:00", "2015-01-01 23:00:00"],
+            "tpep_dropoff_datetime" => ["2015-01-01 08:10:00", "2015-01-01 08:50:00", "2015-01-01 23:15:00"],
+            "trip_distance" => [1.0, 2.0, 3.0],
+            "total_amount" => [10.0, 20.0, 30.0],
+        )
+        .unwrap();
+        let mut etl = PolarsETL::new();
+        etl.df = Some(trips.lazy());
+        etl.clean_data().unwrap();
+
+        assert!(!etl.collect_result("weekly").unwrap());
+        assert!(etl.collect_result("hourly").unwrap());
+        assert_eq!(etl.results.len(), 1);
+        let (stem, hourly) = &etl.results[0];
+        assert_eq!(*stem, "polars_hourly_stats");
+        let counts = hourly.column("trip_count").unwrap().cast(&DataType::Int64).unwrap();
+        assert_eq!(counts.i64().unwrap().into_no_null_iter().collect::<Vec<_>>(), vec![2, 1]);
+
+        // Collecting again replaces the table instead of adding a second copy
+        etl.collect_result("hourly").unwrap();
+        assert_eq!(etl.results.len(), 1);
+    }
+
+    // One trip with the columns `projection` always reads, plus `extra`
+    fn one_trip(extra: Option<(&str, f64)>) -> LazyFrame {
+        let mut trip = df!(
+            "pickup_longitude" => [-73.9],
+            "pickup_latitude" => [40.7],
+            "dropoff_longitude" => [-73.8],
+            "dropoff_latitude" => [40.8],
+            "trip_distance" => [1.5],
+            "passenger_count" => [1i64],
+            "tpep_pickup_datetime" => ["2015-01-01 08:00:00"],
+            "tpep_dropoff_datetime" => ["2015-01-01 08:10:00"],
+            "total_amount" => [12.0],
+        )
+        .unwrap();
+        if let Some((name, value)) = extra {
+            trip.with_column(Column::new(name.into(), [value])).unwrap();
+        }
+        trip.lazy()
+    }
+
+    #[test]
+    fn files_with_different_columns_load_together() {
+        let files = HashMap::from([
+            ("2019.csv".to_string(), one_trip(Some(("congestion_surcharge", 2.5)))),
+            ("2015.csv".to_string(), one_trip(None)),
+        ]);
+        let paths = ["2019.csv".to_string(), "2015.csv".to_string()];
+        let mut etl = PolarsETL::new();
+        etl.load_files(&paths, |_, path| Ok(files[path].clone())).unwrap();
+
+        assert_eq!(etl.surcharge_columns, vec!["congestion_surcharge"]);
+        assert_eq!(etl.metrics["has_surcharge_columns"], 1.0);
+        let loaded = etl.df.clone().unwrap().collect().unwrap();
+        let surcharge = loaded.column("congestion_surcharge").unwrap().f64().unwrap();
+        // The 2015 file has no surcharge column; it reads as no surcharge
+        assert_eq!(surcharge.into_no_null_iter().collect::<Vec<_>>(), vec![2.5, 0.0]);
+    }
+
+    // Hour 8: two trips at 1/min, one at 3/min and one whose dropoff is before its pickup.
+    // Hour 9: only free trips, so the hour's median fare per minute is 0.
+    fn surge_trips() -> DataFrame {
+        df!(
+            "tpep_pickup_datetime" => [
+                "2015-01-01 08:00:00", "2015-01-01 08:05:00", "2015-01-01 08:20:00", "2015-01-01 08:40:00",
+                "2015-01-01 09:00:00", "2015-01-01 09:00:00",
+            ],
+            "tpep_dropoff_datetime" => [
+                "2015-01-01 08:10:00", "2015-01-01 08:15:00", "2015-01-01 08:30:00", "2015-01-01 08:30:00",
+                "2015-01-01 09:10:00", "2015-01-01 09:20:00",
+            ],
+            "fare_amount" => [10.0, 10.0, 30.0, 10.0, 0.0, 0.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn surge_skips_non_positive_durations_and_zero_medians() {
+        for policy in [NegativeDurationPolicy::Drop, NegativeDurationPolicy::ClampZero, NegativeDurationPolicy::Keep] {
+            let mut etl = PolarsETL::new().with_negative_duration_policy(policy);
+            etl.df = Some(surge_trips().lazy());
+            etl.clean_data().unwrap().add_surge_indicator().unwrap();
+
+            assert_eq!(etl.metrics["surge_trips"], 1.0, "{:?}", policy);
+            let out = etl.df.clone().unwrap().collect().unwrap();
+            let surge = out.column("surge_indicator").unwrap().f64().unwrap();
+            let fares = out.column("fare_amount").unwrap().f64().unwrap();
+            for (surge, fare) in surge.into_iter().zip(fares.into_no_null_iter()) {
+                match surge {
+                    Some(v) => assert!(v.is_finite() && v > 0.0, "{:?}: {}", policy, v),
+                    // the reversed trip and the free hour
+                    None => assert!(fare == 10.0 || fare == 0.0, "{:?}", policy),
+                }
+            }
+            assert!(surge.into_iter().flatten().any(|v| (v - 3.0).abs() < 1e-9), "{:?}", policy);
+            let reversed_kept = policy != NegativeDurationPolicy::Drop;
+            assert_eq!(surge.null_count(), 2 + reversed_kept as usize, "{:?}", policy);
+        }
+    }
 }
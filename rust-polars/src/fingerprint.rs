@@ -0,0 +1,63 @@
+use crate::etl::{expand_glob, is_glob};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+// ------- cheap, stable input fingerprint (cache keys, manifests) -------
+
+// Bytes hashed at each of the sample offsets (start, middle, end)
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+// FNV-1a: tiny and, unlike std's DefaultHasher, stable across Rust releases
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+fn hash_file(path: &Path, hasher: &mut Fnv) -> std::io::Result<()> {
+    let meta = std::fs::metadata(path)?;
+    let size = meta.len();
+    hasher.write(&size.to_le_bytes());
+    if let Ok(mtime) = meta.modified()?.duration_since(std::time::UNIX_EPOCH) {
+        hasher.write(&mtime.as_secs().to_le_bytes());
+        hasher.write(&mtime.subsec_nanos().to_le_bytes());
+    }
+
+    let mut file = std::fs::File::open(path)?;
+    let mut buf = Vec::with_capacity(SAMPLE_BYTES as usize);
+    for offset in [0, size.saturating_sub(SAMPLE_BYTES) / 2, size.saturating_sub(SAMPLE_BYTES)] {
+        file.seek(SeekFrom::Start(offset))?;
+        buf.clear();
+        (&mut file).take(SAMPLE_BYTES).read_to_end(&mut buf)?;
+        hasher.write(&buf);
+    }
+    Ok(())
+}
+
+/// Stable key for the data behind `path` (a file, CSV glob or Parquet directory): size,
+/// mtime and three 64 KiB content samples of every file, so a changed or replaced input gets
+/// a new key without reading the whole file. The path itself is not part of the key.
+pub fn input_fingerprint(path: &str) -> std::io::Result<String> {
+    let files = if is_glob(path) {
+        expand_glob(path).map_err(std::io::Error::other)?
+    } else if Path::new(path).is_dir() {
+        expand_glob(&format!("{}/*.parquet", path.trim_end_matches('/'))).map_err(std::io::Error::other)?
+    } else {
+        vec![path.to_string()]
+    };
+
+    let mut hasher = Fnv::new();
+    for file in &files {
+        hash_file(Path::new(file), &mut hasher)?;
+    }
+    Ok(format!("{:016x}", hasher.0))
+}
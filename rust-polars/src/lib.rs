@@ -0,0 +1,15 @@
+//! Polars ETL pipeline shared by the CLI benchmark and the Axum service.
+
+pub mod benchmark;
+pub mod compare;
+pub mod console;
+pub mod engines;
+pub mod etl;
+pub mod fingerprint;
+pub mod manifest;
+#[cfg(feature = "bench-cli")]
+pub mod matrix;
+pub mod profile;
+pub mod progress;
+pub mod queries;
+pub mod synthetic;
@@ -1,49 +1,383 @@
-mod etl;
+#[cfg(feature = "bench-cli")]
+mod cli;
+
+#[cfg(feature = "bench-cli")]
+use polars_etl_benchmark::status;
 
 // =========================
 // CLI benchmark entrypoint
 // =========================
 #[cfg(feature = "bench-cli")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    use etl::PolarsETL;
-    use std::path::Path;
-    use std::time::Instant;
+    use polars_etl_benchmark::manifest::{read_baseline, Manifest};
 
-    println!("{}", "=".repeat(50));
-    println!("🚀 STARTING POLARS ETL BENCHMARK");
-    println!("{}", "=".repeat(50));
+    let raw_args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match cli::parse_args(raw_args.iter().cloned()) {
+        Ok(args) => args,
+        // Asked for help: not an error
+        Err(msg) if msg == cli::USAGE => {
+            println!("{}", msg);
+            return Ok(());
+        }
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(2);
+        }
+    };
+    set_streaming_chunk_size(args.streaming_chunk_size);
 
-    // Check if data file exists
-    let data_file = "../data/yellow_tripdata_2015-01.csv";
-    if !Path::new(data_file).exists() {
-        println!("❌ Data file not found: {}", data_file);
-        println!("Please ensure the NYC taxi dataset is in the data/ directory");
+    if let Some(generate) = &args.generate {
+        if let Err(e) = run_generate(generate) {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
         return Ok(());
     }
 
-    let total_start = Instant::now();
+    if let Some((a, b)) = &args.compare {
+        match run_parity_check(a, b, args.compare_key.as_deref(), &args.tolerances) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
 
-    // Create ETL instance and run pipeline
-    let mut etl = PolarsETL::new();
+    if let Some(path) = &args.replay {
+        match run_replay(path) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    if let Some(concurrent) = &args.concurrent {
+        if let Err(e) = run_concurrent_queries(concurrent) {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+        return Ok(());
+    }
+
+    if let Some(config) = &args.matrix {
+        match run_matrix(config) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    if let Some(compare) = &args.compare_engines {
+        if let Err(e) = run_compare_engines(compare) {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+        return Ok(());
+    }
+
+    if args.validate_only {
+        match run_validate(args) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                eprintln!("❌ Validation failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    match etl
-        .load_data(data_file)?
-        .clean_data()?
-        .aggregate_data()?
-        .sort_and_filter()?
-        .save_results("../results")
+    // Keep stdout for the table alone so it can be piped
+    let summary_md = args.summary_md;
+    polars_etl_benchmark::console::set_status_to_stderr(summary_md);
+
+    // Read before this run overwrites ../results; the baseline may be its last manifest
+    let fail_on_regression = args.fail_on_regression;
+    let baseline_label = args.baseline_label.clone();
+    let baseline = match args
+        .baseline
+        .as_deref()
+        .map(|path| read_baseline(path, baseline_label.as_deref()))
+        .transpose()
     {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let Some(etl) = run_benchmark(args)? else {
+        // Nothing ran, so nothing was checked; a regression gate must not pass on that
+        if fail_on_regression.is_some() {
+            eprintln!("❌ The benchmark did not complete; no stage timings to check against the baseline");
+            std::process::exit(1);
+        }
+        return Ok(());
+    };
+    let path = "../results/benchmark_manifest.json";
+    Manifest::capture(raw_args, &etl).write(path)?;
+    status!("🧾 Manifest written to {}", path);
+    if summary_md {
+        print!("{}", etl.summary_markdown());
+    }
+    if let Some(baseline) = &baseline {
+        if !report_regressions(baseline, &etl, fail_on_regression) {
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+// The streaming engine reads its chunk size once per process, so it goes in the environment
+// before anything is collected instead of per pipeline
+#[cfg(feature = "bench-cli")]
+fn set_streaming_chunk_size(rows: Option<usize>) {
+    if let Some(rows) = rows {
+        std::env::set_var(polars_etl_benchmark::etl::MORSEL_SIZE_ENV, rows.to_string());
+    }
+}
+
+// Warn when the input's fingerprint differs from the one recorded with an earlier run: its
+// results (and timings) came from other data
+#[cfg(feature = "bench-cli")]
+fn warn_if_input_changed(recorded: Option<&String>, current: Option<&String>, since: &str) {
+    if recorded != current {
+        status!(
+            "⚠️  Input changed since {} (fingerprint {} → {})",
+            since,
+            recorded.map_or("-", |f| f.as_str()),
+            current.map_or("-", |f| f.as_str())
+        );
+    }
+}
+
+// Stage timings against the baseline; with a limit, GitHub Actions annotations for the stages
+// past it (error) or past half of it (warning). false when any stage failed the limit, or when
+// a limit is set but no stage timing is shared with the baseline.
+#[cfg(feature = "bench-cli")]
+fn report_regressions(
+    baseline: &polars_etl_benchmark::manifest::Baseline,
+    etl: &polars_etl_benchmark::etl::PolarsETL,
+    limit_pct: Option<f64>,
+) -> bool {
+    use polars_etl_benchmark::manifest::{stage_regressions, RegressionLevel};
+
+    // Timings on other data don't say much about the code; the check still runs
+    warn_if_input_changed(
+        baseline.tags.get("input_fingerprint"),
+        etl.get_tags().get("input_fingerprint"),
+        "the baseline run; its timings may not be comparable",
+    );
+    let current = etl.get_metrics();
+
+    let checks = stage_regressions(&baseline.metrics, current, limit_pct.unwrap_or(f64::INFINITY));
+    if checks.is_empty() {
+        if limit_pct.is_some() {
+            status!("❌ No stage timings in common with the baseline; nothing to check the limit against");
+            return false;
+        }
+        status!("⚠️  No stage timings in common with the baseline");
+        return true;
+    }
+    match baseline.label() {
+        Some(label) => status!("\n📉 Stage timings vs. baseline {} ('{}'):", baseline.path, label),
+        None => status!("\n📉 Stage timings vs. baseline {}:", baseline.path),
+    }
+    for (d, level) in &checks {
+        let mark = match level {
+            RegressionLevel::Pass => "✅",
+            RegressionLevel::Warn => "⚠️ ",
+            RegressionLevel::Fail => "❌",
+        };
+        status!(
+            "  {} {:<20} {:>10.3}s → {:>10.3}s ({:+.1}%)",
+            mark,
+            d.metric,
+            d.recorded,
+            d.replayed,
+            d.change_pct()
+        );
+    }
+
+    let Some(limit) = limit_pct else {
+        return true;
+    };
+    // The Actions runner picks workflow commands up from stdout and stderr alike; status! keeps
+    // them off stdout when that carries the Markdown summary
+    let mut passed = true;
+    for (d, level) in &checks {
+        let command = match level {
+            RegressionLevel::Pass => continue,
+            RegressionLevel::Warn => "warning",
+            RegressionLevel::Fail => {
+                passed = false;
+                "error"
+            }
+        };
+        status!(
+            "::{} title=Benchmark regression in {}::{} is {:.1}% slower than the baseline ({:.3}s → {:.3}s; limit {}%)",
+            command,
+            d.metric.trim_end_matches("_time"),
+            d.metric,
+            d.change_pct(),
+            d.recorded,
+            d.replayed,
+            limit
+        );
+    }
+    passed
+}
+
+// Full pipeline as configured on the command line; None when the input is missing or
+// the results could not be saved
+#[cfg(feature = "bench-cli")]
+fn run_benchmark(
+    args: cli::CliArgs,
+) -> Result<Option<polars_etl_benchmark::etl::PolarsETL>, Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::etl::{input_exists, OutputFormat, PolarsETL, Stage};
+    use std::time::{Duration, Instant};
+
+    status!("{}", "=".repeat(50));
+    status!("🚀 STARTING POLARS ETL BENCHMARK");
+    status!("{}", "=".repeat(50));
+
+    // Check if data file exists
+    let data_file = args.input.as_deref().unwrap_or("../data/yellow_tripdata_2015-01.csv");
+    if !input_exists(data_file) {
+        status!("❌ Data file not found: {}", data_file);
+        status!("Please ensure the NYC taxi dataset is in the data/ directory");
+        return Ok(None);
+    }
+
+    // Create ETL instance and run pipeline
+    let mut etl = PolarsETL::new()
+        .with_sample_size(args.sample_size)
+        .with_label(args.label)
+        .with_hot_columns(args.hot_columns)
+        .with_trip_sets(args.trip_sets)
+        .with_units(args.units)
+        .with_deterministic_sums(args.deterministic_sums)
+        .with_extra_columns(args.extra_columns)
+        .with_negative_duration_policy(args.negative_durations)
+        .with_declared_period(args.period, args.period_margin_days.unwrap_or(1))
+        .with_out_of_period(args.out_of_period)
+        .with_optimizations(args.optimizations)
+        .with_decimal_comma(args.decimal_comma)
+        .with_coerce_types(args.coerce_types)
+        .with_streaming(args.streaming_chunk_size.is_some())
+        .with_reorder_columns(args.reorder_columns)
+        .with_use_columns(args.use_columns)
+        .with_approximate_quantiles(args.approximate_quantiles);
+    if let Some(n) = args.quantile_sample_rows {
+        etl = etl.with_quantile_sample_rows(n);
+    }
+    if !args.agg_specs.is_empty() {
+        etl = etl.with_agg_specs(args.agg_specs);
+    }
+    if let Some(n) = args.file_read_concurrency {
+        etl = etl.with_file_read_concurrency(n);
+    }
+    if let Some(separator) = args.separator {
+        etl = etl.with_separator(separator);
+    }
+
+    // Reference workload on the still idle machine; not part of the total
+    etl.record_machine_score();
+    let total_start = Instant::now();
+
+    let stages = args.stages.unwrap_or_else(|| Stage::ALL.to_vec());
+    let run = |stage| stages.contains(&stage);
+    if run(Stage::Load) {
+        if let Some(secs) = args.budget_secs {
+            etl.auto_sample_for_duration(data_file, Duration::from_secs_f64(secs))?;
+        }
+        if let Some((start, len)) = args.byte_range {
+            etl.load_byte_range(data_file, start, len)?;
+        } else {
+            etl.load_input(data_file)?;
+        }
+    }
+    if run(Stage::Clean) {
+        etl.clean_data()?.pin_hot_columns()?;
+        if args.audit {
+            etl.write_audit("../results/audit.json")?;
+        }
+        if args.surge_indicator {
+            etl.add_surge_indicator()?;
+        }
+        if args.quantiles {
+            etl.compute_quantiles()?;
+        }
+        for query in args.queries {
+            etl.run_named_query(query)?;
+        }
+    }
+    if run(Stage::Aggregate) {
+        etl.aggregate_data()?;
+        if args.night_split {
+            etl.aggregate_by_night()?;
+        }
+    }
+    if args.explain_analyze {
+        etl.explain_analyze()?;
+    }
+    if run(Stage::SortFilter) {
+        etl.sort_and_filter()?;
+    }
+    if let Some(rows) = args.preview {
+        status!("\n👀 Result preview:\n{}", etl.preview(rows, args.date_display.as_deref())?);
+    }
+    for format in args.roundtrip {
+        etl.bench_roundtrip(format)?;
+    }
+    for bench in args.benches {
+        match bench {
+            cli::Bench::TypedVsString => etl.compare_typed_vs_string()?,
+            cli::Bench::GroupbyStability => etl.compare_groupby_stability()?,
+            cli::Bench::LazyOverhead => etl.measure_lazy_overhead()?,
+        };
+    }
+    if let Some(lookup) = &args.join_lookup {
+        etl.compare_join_strategies(lookup)?;
+    }
+
+    let output_format = args.output_format.unwrap_or(OutputFormat::Csv);
+    let saved = if run(Stage::Save) {
+        etl.save_results_as("../results", output_format)
+    } else {
+        Ok(())
+    };
+    match saved {
         Ok(_) => {
             let total_time = total_start.elapsed().as_secs_f64();
 
             // Final summary
-            println!("\n{}", "=".repeat(50));
-            println!("🎉 POLARS BENCHMARK COMPLETE!");
-            println!("{}", "=".repeat(50));
-            println!("⏱️  Total time: {:.2} seconds", total_time);
+            status!("\n{}", "=".repeat(50));
+            status!("🎉 POLARS BENCHMARK COMPLETE!");
+            status!("{}", "=".repeat(50));
+            status!("⏱️  Total time: {:.2} seconds", total_time);
+            if let Some(target) = etl.get_metrics().get("budget_target_time") {
+                let actual: f64 = ["load_time", "clean_time", "aggregate_time", "sort_filter_time"]
+                    .iter()
+                    .filter_map(|k| etl.get_metrics().get(*k))
+                    .sum();
+                status!(
+                    "🎯 Budget {:.1}s: {} rows, load → sort_filter took {:.2}s",
+                    target,
+                    etl.get_metrics().get("budget_sample_rows").copied().unwrap_or(0.0),
+                    actual
+                );
+            }
 
             // Show key performance metrics
-            println!("\n📈 Key Performance Metrics:");
+            status!("\n📈 Key Performance Metrics:");
             let metrics = etl.get_metrics();
             for (key, value) in metrics {
                 if key.contains("time") {
@@ -61,34 +395,483 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         })
                         .collect::<Vec<String>>()
                         .join(" ");
-                    println!("  {}: {:.2}s", formatted_key, value);
+                    match etl.normalized(*value) {
+                        Some(n) => status!("  {}: {:.2}s ({:.1} ÷ machine score)", formatted_key, value, n),
+                        None => status!("  {}: {:.2}s", formatted_key, value),
+                    }
                 }
             }
 
-            println!("{}", "=".repeat(50));
+            status!("{}", "=".repeat(50));
         }
         Err(e) => {
-            println!("❌ Error during Polars benchmark: {}", e);
+            status!("❌ Error during Polars benchmark: {}", e);
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(etl))
+}
+
+
+// Rows read by --validate-only
+#[cfg(feature = "bench-cli")]
+const VALIDATE_ROWS: usize = 100;
+
+// Wire up the pipeline as configured but run it on a tiny sample and save nothing: a fast
+// "does it still work" check for CI and pre-commit hooks
+#[cfg(feature = "bench-cli")]
+fn run_validate(args: cli::CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::etl::PolarsETL;
+    use std::time::Instant;
+
+    let data_file = args.input.as_deref().unwrap_or("../data/yellow_tripdata_2015-01.csv");
+    let start = Instant::now();
+    let mut etl = PolarsETL::new()
+        .with_sample_size(Some(VALIDATE_ROWS))
+        .with_units(args.units)
+        .with_deterministic_sums(args.deterministic_sums)
+        .with_extra_columns(args.extra_columns)
+        .with_negative_duration_policy(args.negative_durations)
+        .with_declared_period(args.period, args.period_margin_days.unwrap_or(1))
+        .with_out_of_period(args.out_of_period)
+        .with_optimizations(args.optimizations)
+        .with_decimal_comma(args.decimal_comma)
+        .with_coerce_types(args.coerce_types)
+        .with_use_columns(args.use_columns)
+        .with_reorder_columns(args.reorder_columns);
+    if !args.agg_specs.is_empty() {
+        etl = etl.with_agg_specs(args.agg_specs);
+    }
+    if let Some(separator) = args.separator {
+        etl = etl.with_separator(separator);
+    }
+
+    etl.load_input(data_file)?;
+    etl.validate_schema()?.clean_data()?.aggregate_data()?.sort_and_filter()?;
+    println!(
+        "✅ Pipeline valid on {} sample rows in {:.2}s",
+        VALIDATE_ROWS,
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+// Re-run the configuration recorded in a manifest and diff the metrics; Ok(false) when
+// a result metric (counts, rows) no longer matches
+#[cfg(feature = "bench-cli")]
+fn run_replay(path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::manifest::{diff_metrics, Manifest, THREAD_ENV};
+
+    let manifest = Manifest::read(path).map_err(|e| format!("cannot read manifest {}: {}", path, e))?;
+    println!("🔁 Replaying run from {} ({})", manifest.created_at, path);
+    println!("   args: {}", manifest.args.join(" "));
+
+    // Thread caps must be in place before Polars builds its pool
+    for key in THREAD_ENV {
+        match manifest.env.get(key) {
+            Some(value) => std::env::set_var(key, value),
+            None => std::env::remove_var(key),
+        }
+    }
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if cores != manifest.available_parallelism {
+        println!(
+            "⚠️  Recorded on {} cores, replaying on {}; timings are not directly comparable",
+            manifest.available_parallelism, cores
+        );
+    }
+
+    let mut args = cli::parse_args(manifest.args.iter().cloned())?;
+    // A budget run sized its sample by timing the machine it ran on; recalibrating here
+    // would pick another size, so replay the size it chose
+    if args.budget_secs.take().is_some() {
+        let rows = manifest
+            .metrics
+            .get("budget_sample_rows")
+            .ok_or("the manifest has --budget but no budget_sample_rows to replay it with")?;
+        println!("   replaying --budget as --sample-size {}", rows);
+        args.sample_size = Some(*rows as usize);
+    }
+    set_streaming_chunk_size(args.streaming_chunk_size);
+    let Some(etl) = run_benchmark(args)? else {
+        return Err("replay did not complete".into());
+    };
+    warn_if_input_changed(
+        manifest.tags.get("input_fingerprint"),
+        etl.get_tags().get("input_fingerprint"),
+        "the recorded run",
+    );
+
+    let drift = diff_metrics(&manifest.metrics, etl.get_metrics());
+    let mut reproduced = true;
+    println!("\n📐 Drift vs. recorded run:");
+    for d in &drift {
+        let mark = if d.is_measurement() {
+            "  "
+        } else if d.recorded == d.replayed {
+            "✅"
+        } else {
+            reproduced = false;
+            "❌"
+        };
+        println!(
+            "  {} {:<36} {:>14.4} → {:>14.4} ({:+.1}%)",
+            mark,
+            d.metric,
+            d.recorded,
+            d.replayed,
+            d.change_pct()
+        );
+    }
+    println!(
+        "{}",
+        if reproduced {
+            "✅ Result metrics reproduced"
+        } else {
+            "❌ Result metrics differ from the recorded run"
         }
+    );
+    Ok(reproduced)
+}
+
+// Load and clean the input, then run the concurrent query mix against it
+#[cfg(feature = "bench-cli")]
+fn run_concurrent_queries(args: &cli::ConcurrentArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::etl::{input_exists, PolarsETL};
+    use std::time::Duration;
+
+    let data_file = args.input.as_deref().unwrap_or("../data/yellow_tripdata_2015-01.csv");
+    if !input_exists(data_file) {
+        return Err(format!("data file not found: {}", data_file).into());
     }
 
+    let mut etl = PolarsETL::new();
+    etl.load_input(data_file)?.clean_data()?;
+    etl.bench_concurrent_queries(
+        args.queries.clone(),
+        args.concurrency,
+        Duration::from_secs(args.duration_secs),
+    )?;
     Ok(())
 }
 
+// Run the full pipeline on Polars, then on every other engine that is installed, and
+// print the per-stage times side by side with a summary of where the gaps come from
+#[cfg(feature = "bench-cli")]
+fn run_compare_engines(compare: &cli::CompareEnginesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::engines::{
+        engines_table, run_duckdb, run_pandas, ComparisonResult, EngineRun, SpeedupNarrator,
+    };
+
+    let data_file = compare.input.clone().unwrap_or_else(|| "../data/yellow_tripdata_2015-01.csv".into());
+    let args = cli::CliArgs { input: Some(data_file.clone()), ..Default::default() };
+    let Some(etl) = run_benchmark(args)? else {
+        return Err("the Polars run did not complete".into());
+    };
+    let mut runs = vec![EngineRun::from_metrics("polars", etl.get_metrics())];
+
+    let mut skipped = Vec::new();
+    println!("\n🐼 Running pandas...");
+    match run_pandas(&data_file, "../python-pandas", "../results") {
+        Ok(run) => runs.push(run),
+        Err(reason) => skipped.push(("pandas", reason)),
+    }
+    match run_duckdb(&data_file) {
+        Ok(run) => runs.push(run),
+        Err(reason) => skipped.push(("duckdb", reason)),
+    }
+
+    println!("\n🏁 Engine comparison on {} (seconds; vs <engine> = how many times faster Polars is)\n", data_file);
+    print!("{}", engines_table(&runs));
+    if let Some(score) = etl.get_metrics().get("machine_score") {
+        // Same host for every engine, so one score normalizes them all
+        println!("\n🖥️  Machine score {:.4}s; total ÷ machine score:", score);
+        for run in &runs {
+            println!("  {:<10} {:.1}", run.engine, run.total() / score);
+        }
+    }
+    let mut narrator = SpeedupNarrator::new();
+    if let Some(band) = compare.parity_band {
+        narrator = narrator.with_parity_band(band);
+    }
+    for other in &runs[1..] {
+        let comparison = ComparisonResult { polars: runs[0].clone(), other: other.clone() };
+        println!("\n📝 {}", narrator.explain_speedup(&comparison));
+    }
+    for (engine, reason) in skipped {
+        println!("⏭️  Skipped {}: {}", engine, reason);
+    }
+    Ok(())
+}
+
+// Run every cell of a benchmark matrix as its own run of this binary (thread caps only take
+// effect before Polars builds its pool) and append each cell's manifest metrics to one JSON
+// Lines file; Ok(false) when a cell failed
+#[cfg(feature = "bench-cli")]
+fn run_matrix(path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::manifest::{Manifest, THREAD_ENV};
+    use polars_etl_benchmark::matrix::{MatrixConfig, MatrixRecord};
+    use std::io::Write;
+    use std::process::Command;
+
+    let config = MatrixConfig::read(path)?;
+    let input = config.input.clone().unwrap_or_else(|| "../data/yellow_tripdata_2015-01.csv".into());
+    let cells = config.cells();
+    let total = cells.len();
+    let exe = std::env::current_exe()?;
+    let manifest_path = "../results/benchmark_manifest.json";
+    let mut out = std::fs::File::create(&config.output)
+        .map_err(|e| format!("cannot create {}: {}", config.output, e))?;
+
+    println!("🧮 Benchmark matrix from {}: {} cells on {}", path, total, input);
+    let mut failed = 0;
+    for (i, cell) in cells.into_iter().enumerate() {
+        println!("\n▶️  Cell {}/{}: {}", i + 1, total, cell.label());
+        // A failed cell must not pick up the previous cell's manifest
+        let _ = std::fs::remove_file(manifest_path);
+        let mut command = Command::new(&exe);
+        command.args(cell.args(&input)).args(&config.args);
+        if let Some(n) = cell.threads {
+            for key in THREAD_ENV {
+                command.env(key, n.to_string());
+            }
+        }
+        let status = command.status()?;
+
+        let (metrics, error) = if !status.success() {
+            (Default::default(), Some(format!("exited with {}", status)))
+        } else {
+            match Manifest::read(manifest_path) {
+                Ok(manifest) => (manifest.metrics, None),
+                Err(e) => (Default::default(), Some(format!("no manifest written: {}", e))),
+            }
+        };
+        if let Some(e) = &error {
+            failed += 1;
+            println!("❌ Cell {} failed: {}", i + 1, e);
+        }
+        let record = MatrixRecord { cell, error, metrics };
+        writeln!(out, "{}", serde_json::to_string(&record)?)?;
+    }
+
+    println!(
+        "\n🧮 {} cells written to {}{}",
+        total,
+        config.output,
+        if failed > 0 { format!(" ({} failed)", failed) } else { String::new() }
+    );
+    Ok(failed == 0)
+}
+
+// Write a synthetic dataset in the format given by the output extension
+#[cfg(feature = "bench-cli")]
+fn run_generate(args: &cli::GenerateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::etl::{write_frame, OutputFormat};
+    use polars_etl_benchmark::synthetic::generate_synthetic;
+    use std::time::Instant;
+
+    let ext = std::path::Path::new(&args.out).extension().and_then(|e| e.to_str()).unwrap_or("csv");
+    let format: OutputFormat = ext.parse()?;
+
+    let start = Instant::now();
+    let mut df = generate_synthetic(args.rows, args.seed)?;
+    write_frame(&mut df, &args.out, format)?;
+    println!(
+        "✅ Wrote {} synthetic trips (seed {}) to {} in {:.2}s",
+        args.rows,
+        args.seed,
+        args.out,
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+// Semantic diff of two result files; Ok(false) when they differ
+#[cfg(feature = "bench-cli")]
+fn run_parity_check(
+    a: &str,
+    b: &str,
+    key: Option<&str>,
+    tolerances: &polars_etl_benchmark::compare::ToleranceConfig,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    use polars_etl_benchmark::compare::compare_dataframes;
+    use polars_etl_benchmark::etl::{read_frame, OutputFormat};
+
+    let read = |path: &str| {
+        let ext = std::path::Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("csv");
+        let format: OutputFormat = ext.parse()?;
+        read_frame(path, format).map_err(|e| e.to_string())
+    };
+    let (df_a, df_b) = (read(a)?, read(b)?);
+    let key = match key {
+        Some(key) => key.to_string(),
+        None => df_a.get_column_names().first().ok_or("first file has no columns")?.to_string(),
+    };
+
+    println!("🔍 A: {}\n🔍 B: {}", a, b);
+    let report = compare_dataframes(&df_a, &df_b, &key, tolerances)?;
+    println!("{}", report);
+    Ok(report.is_match())
+}
+
 // =========================
 // Shuttle web API (handlers)
 // =========================
 #[cfg(not(feature = "bench-cli"))]
 mod shuttle_app {
-    use axum::{extract::Query, http::StatusCode, response::Json, routing::get, Router};
+    use axum::{
+        body::Body,
+        extract::{Path as RoutePath, Query},
+        http::{header, HeaderMap, HeaderName, StatusCode},
+        response::{
+            sse::{Event, KeepAlive, Sse},
+            Html, IntoResponse, Json, Response,
+        },
+    };
+    use polars_etl_benchmark::engines::{read_engine_run, ComparisonResult, EngineRun, SpeedupNarrator};
+    use polars_etl_benchmark::etl::{
+        input_exists, ExtraColumnPolicy, NegativeDurationPolicy, OutputFormat, PolarsETL, Stage, Units,
+    };
+    use polars_etl_benchmark::fingerprint::input_fingerprint;
+    use polars_etl_benchmark::progress::{ProgressEvent, ProgressHook};
     use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
-    use tower_http::cors::CorsLayer;
+    use std::convert::Infallible;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+    use std::time::{Duration, Instant};
+    use tokio_util::io::ReaderStream;
 
-    #[derive(Deserialize)]
+    // Self-contained landing page served to browsers hitting `/`
+    const LANDING_PAGE: &str = include_str!("landing.html");
+    const FAVICON_SVG: &str = include_str!("favicon.svg");
+
+    // Dataset (file, CSV glob or Parquet dir) and per-run output locations (override via env)
+    fn data_file() -> String {
+        std::env::var("DATA_FILE").unwrap_or_else(|_| "../data/yellow_tripdata_2015-01.csv".into())
+    }
+
+    // pandas metrics the speedup summary compares against (`PANDAS_METRICS` overrides)
+    fn pandas_metrics_file() -> PathBuf {
+        std::env::var("PANDAS_METRICS")
+            .unwrap_or_else(|_| "../results/pandas_metrics.json".into())
+            .into()
+    }
+
+    fn runs_dir() -> PathBuf {
+        std::env::var("RUNS_DIR")
+            .unwrap_or_else(|_| "../results/runs".into())
+            .into()
+    }
+
+    // Run directories kept under RUNS_DIR; the oldest are removed as new runs start.
+    // `KEEP_RUNS=0` keeps everything.
+    fn keep_runs() -> Option<usize> {
+        match std::env::var("KEEP_RUNS").ok().and_then(|v| v.parse().ok()) {
+            Some(0) => None,
+            Some(n) => Some(n),
+            None => Some(20),
+        }
+    }
+
+    // Wall-clock cap for web-triggered runs so one oversized request can't hold the shared
+    // instance; `MAX_RUN_SECONDS=0` disables it. The CLI is never capped.
+    fn max_run_seconds() -> Option<u64> {
+        match std::env::var("MAX_RUN_SECONDS").ok().and_then(|v| v.parse().ok()) {
+            Some(0) => None,
+            Some(secs) => Some(secs),
+            None => Some(600),
+        }
+    }
+
+    #[derive(Clone, Deserialize)]
     pub struct BenchmarkQuery {
         #[serde(default)]
         pub sample_size: Option<usize>,
+        #[serde(default)]
+        pub label: Option<String>,
+        // Comma-separated columns to keep materialized after cleaning (e.g. fare_amount,tip_amount)
+        #[serde(default)]
+        pub hot_columns: Option<String>,
+        // imperial (miles, default) or metric (km) distances in the results
+        #[serde(default)]
+        pub units: Units,
+        // Exact cent sums for money columns (reproducible across thread counts)
+        #[serde(default)]
+        pub deterministic_sums: bool,
+        // ignore (default), include or error on columns outside the taxi schema
+        #[serde(default)]
+        pub extra_columns: ExtraColumnPolicy,
+        // drop (default), clamp_zero or keep trips whose dropoff is before the pickup
+        #[serde(default)]
+        pub negative_durations: NegativeDurationPolicy,
+        // Pick sample_size so the core stages take about this many seconds (overrides sample_size)
+        #[serde(default)]
+        pub budget_seconds: Option<f64>,
+        // Comma-separated subset of load,clean,aggregate,sort_filter,save (default: all)
+        #[serde(default)]
+        pub stages: Option<String>,
+    }
+
+    impl BenchmarkQuery {
+        fn stages(&self) -> Result<Vec<Stage>, String> {
+            match &self.stages {
+                Some(list) => Stage::parse_list(list),
+                None => Ok(Stage::ALL.to_vec()),
+            }
+        }
+    }
+
+    /// Parameters of `/plan/analyze`, `/results/arrow` and `/results/stream`, which load, clean
+    /// and compute a single table but never save. Anything else is rejected with `400`.
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct ResultRunQuery {
+        #[serde(default)]
+        pub sample_size: Option<usize>,
+        #[serde(default)]
+        pub units: Units,
+        #[serde(default)]
+        pub deterministic_sums: bool,
+        #[serde(default)]
+        pub extra_columns: ExtraColumnPolicy,
+        #[serde(default)]
+        pub negative_durations: NegativeDurationPolicy,
+        // daily (default), hourly, dow or night; /results/* only
+        #[serde(default)]
+        pub result: Option<String>,
+    }
+
+    impl ResultRunQuery {
+        fn result(&self) -> Result<String, String> {
+            match self.result.as_deref().unwrap_or("daily") {
+                name @ ("daily" | "hourly" | "dow" | "night") => Ok(name.to_string()),
+                _ => Err("result must be daily, hourly, dow or night".into()),
+            }
+        }
+
+        // Load and clean with these options, reporting the stages to `hook`
+        fn load_and_clean(
+            &self,
+            data_file: &str,
+            hook: ProgressHook,
+            abort: &AtomicBool,
+        ) -> Result<PolarsETL, String> {
+            let mut etl = PolarsETL::new()
+                .with_sample_size(self.sample_size)
+                .with_units(self.units)
+                .with_deterministic_sums(self.deterministic_sums)
+                .with_extra_columns(self.extra_columns)
+                .with_negative_duration_policy(self.negative_durations)
+                .with_progress(hook);
+            check_abort(abort, "load")?;
+            etl.load_input(data_file).map_err(|e| e.to_string())?;
+            check_abort(abort, "clean")?;
+            etl.clean_data().map_err(|e| e.to_string())?;
+            Ok(etl)
+        }
     }
 
     #[derive(Serialize)]
@@ -97,6 +880,20 @@ mod shuttle_app {
         pub message: String,
         pub performance_summary: String,
         pub dataset_info: DatasetInfo,
+        pub label: Option<String>,
+        pub run_id: Option<String>,  // set when a real run wrote output files
+        pub input_fingerprint: Option<String>, // changes whenever the dataset does; use as a cache key
+        pub downloads: Vec<String>,  // `/download/{run_id}/{filename}` links for those files
+    }
+
+    /// Body of the `504` returned when a run exceeds `MAX_RUN_SECONDS`.
+    #[derive(Serialize)]
+    pub struct TimeoutReport {
+        pub timed_out: bool,
+        pub max_run_seconds: u64,
+        pub run_id: String,
+        pub stage: Option<&'static str>,   // stage in progress when the limit was hit
+        pub metrics: HashMap<String, f64>, // `<stage>_time` of the stages that finished
     }
 
     #[derive(Serialize)]
@@ -114,12 +911,550 @@ mod shuttle_app {
         pub version: String,
         pub description: String,
         pub endpoints: Vec<String>,
+        pub supported_formats: Vec<String>, // output formats compiled into this build
+    }
+
+    fn dataset_info() -> DatasetInfo {
+        DatasetInfo {
+            name: "NYC Yellow Taxi Data (January 2015)".to_string(),
+            rows: 12_748_986,
+            size_mb: "~2.1 GB".to_string(),
+            columns: 19,
+        }
+    }
+
+    // Timestamp plus a process-wide sequence number, so requests landing in the same
+    // millisecond still get their own directory. Ids sort by start time.
+    fn new_run_id() -> String {
+        static SEQ: AtomicU64 = AtomicU64::new(0);
+        format!(
+            "{}-{:06}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ"),
+            SEQ.fetch_add(1, Ordering::Relaxed) % 1_000_000
+        )
+    }
+
+    // Delete the oldest run directories until at most `keep` remain; runs still in
+    // progress are never touched.
+    fn prune_runs(keep: usize) {
+        let dir = runs_dir();
+        let Ok(entries) = std::fs::read_dir(&dir) else { return };
+        let mut ids: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .collect();
+        ids.sort();
+        let excess = ids.len().saturating_sub(keep);
+        let live: Vec<String> = lock(live_runs()).keys().cloned().collect();
+        for id in ids.into_iter().take(excess).filter(|id| !live.contains(id)) {
+            if let Err(e) = std::fs::remove_dir_all(dir.join(&id)) {
+                eprintln!("⚠️  Could not remove old run {}: {}", id, e);
+            }
+        }
+    }
+
+    // Err once `abort` is set, so a timed-out run starts no further stage
+    fn check_abort(abort: &AtomicBool, stage: &str) -> Result<(), String> {
+        if abort.load(Ordering::Relaxed) {
+            return Err(format!("aborted before '{}': run exceeded MAX_RUN_SECONDS", stage));
+        }
+        Ok(())
+    }
+
+    // Full pipeline into `out_dir`; errors are flattened to strings so the result
+    // can leave spawn_blocking. Once `abort` is set no further stage is started.
+    fn run_pipeline(
+        data_file: &str,
+        out_dir: &Path,
+        query: BenchmarkQuery,
+        progress: Option<ProgressHook>,
+        abort: &AtomicBool,
+    ) -> Result<HashMap<String, f64>, String> {
+        let out_dir = out_dir.to_str().ok_or("output path is not valid UTF-8")?;
+        let start = Instant::now();
+
+        let stages = query.stages()?;
+        let hot_columns = query
+            .hot_columns
+            .as_deref()
+            .map(|cols| cols.split(',').map(|c| c.trim().to_string()).collect())
+            .unwrap_or_default();
+        let mut etl = PolarsETL::new()
+            .with_sample_size(query.sample_size)
+            .with_label(query.label)
+            .with_hot_columns(hot_columns)
+            .with_units(query.units)
+            .with_deterministic_sums(query.deterministic_sums)
+            .with_extra_columns(query.extra_columns)
+            .with_negative_duration_policy(query.negative_durations);
+        if let Some(hook) = progress {
+            etl = etl.with_progress(hook);
+        }
+        let run = |stage: Stage| {
+            check_abort(abort, stage.name())?;
+            Ok(stages.contains(&stage))
+        };
+        if run(Stage::Load)? {
+            if let Some(secs) = query.budget_seconds.filter(|s| *s > 0.0 && s.is_finite()) {
+                etl.auto_sample_for_duration(data_file, Duration::from_secs_f64(secs))
+                    .map_err(|e| e.to_string())?;
+            }
+            etl.load_input(data_file).map_err(|e| e.to_string())?;
+        }
+        if run(Stage::Clean)? {
+            etl.clean_data().and_then(|e| e.pin_hot_columns()).map_err(|e| e.to_string())?;
+        }
+        if run(Stage::Aggregate)? {
+            etl.aggregate_data().map_err(|e| e.to_string())?;
+        }
+        if run(Stage::SortFilter)? {
+            etl.sort_and_filter().map_err(|e| e.to_string())?;
+        }
+        if run(Stage::Save)? {
+            etl.save_results(out_dir).map_err(|e| e.to_string())?;
+        }
+
+        let mut metrics = etl.get_metrics().clone();
+        metrics.insert("total_time".into(), start.elapsed().as_secs_f64());
+        Ok(metrics)
+    }
+
+    // Stage in progress and timings of finished stages, kept up to date from the progress events
+    struct RunTracker {
+        started: Instant,
+        stage: Option<&'static str>,
+        metrics: HashMap<String, f64>,
+    }
+
+    impl RunTracker {
+        fn new() -> Self {
+            Self { started: Instant::now(), stage: None, metrics: HashMap::new() }
+        }
+
+        fn record(&mut self, event: &ProgressEvent) {
+            match event {
+                ProgressEvent::StageStarted { stage } => self.stage = Some(*stage),
+                ProgressEvent::StageFinished { stage, seconds } => {
+                    self.stage = None;
+                    self.metrics.insert(format!("{}_time", stage), *seconds);
+                }
+            }
+        }
+    }
+
+    // Runs in flight by run id, for /benchmark/current
+    fn live_runs() -> &'static Mutex<HashMap<String, Arc<Mutex<RunTracker>>>> {
+        static RUNS: OnceLock<Mutex<HashMap<String, Arc<Mutex<RunTracker>>>>> = OnceLock::new();
+        RUNS.get_or_init(Default::default)
+    }
+
+    // The guarded data even if a run panicked while holding the lock: the run list and the
+    // trackers are plain data that stay consistent between statements
+    fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Keeps a run listed in `live_runs` until dropped, including by a panic unwinding
+    struct LiveRunEntry(String);
+
+    impl LiveRunEntry {
+        fn register(run_id: &str, tracker: Arc<Mutex<RunTracker>>) -> Self {
+            lock(live_runs()).insert(run_id.to_string(), tracker);
+            Self(run_id.to_string())
+        }
+    }
+
+    impl Drop for LiveRunEntry {
+        fn drop(&mut self) {
+            lock(live_runs()).remove(&self.0);
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct LiveRun {
+        pub run_id: String,
+        pub stage: Option<&'static str>, // None between stages
+        pub elapsed_seconds: f64,
+        pub metrics: HashMap<String, f64>, // `<stage>_time` of the finished stages
+    }
+
+    /// Run `work` on the blocking pool under `MAX_RUN_SECONDS`, listed in `/benchmark/current`
+    /// until it returns. `work` gets the hook that keeps that listing current (and forwards
+    /// to `progress`) and the abort flag. On timeout the flag is set, so the run stops at the
+    /// next stage boundary (a stage already collecting can't be interrupted), and the partial
+    /// progress is returned as `Err`.
+    async fn run_limited<T, F>(
+        run_id: &str,
+        progress: Option<ProgressHook>,
+        work: F,
+    ) -> Result<Result<T, String>, TimeoutReport>
+    where
+        T: Send + 'static,
+        F: FnOnce(ProgressHook, &AtomicBool) -> Result<T, String> + Send + 'static,
+    {
+        let tracker = Arc::new(Mutex::new(RunTracker::new()));
+        let abort = Arc::new(AtomicBool::new(false));
+        let entry = LiveRunEntry::register(run_id, tracker.clone());
+
+        let tracked = tracker.clone();
+        let hook: ProgressHook = Arc::new(move |event: &ProgressEvent| {
+            lock(&tracked).record(event);
+            if let Some(forward) = &progress {
+                forward(event);
+            }
+        });
+
+        let run_abort = abort.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            // A timed-out run stays listed until it has actually stopped
+            let _entry = entry;
+            work(hook, &run_abort)
+        });
+        let joined = match max_run_seconds() {
+            Some(limit) => match tokio::time::timeout(Duration::from_secs(limit), task).await {
+                Ok(joined) => joined,
+                Err(_) => {
+                    abort.store(true, Ordering::Relaxed);
+                    let t = lock(&tracker);
+                    return Err(TimeoutReport {
+                        timed_out: true,
+                        max_run_seconds: limit,
+                        run_id: run_id.to_string(),
+                        stage: t.stage,
+                        metrics: t.metrics.clone(),
+                    });
+                }
+            },
+            None => task.await,
+        };
+        Ok(joined.unwrap_or_else(|e| Err(e.to_string())))
+    }
+
+    /// The full pipeline under [`run_limited`], writing into `out_dir`.
+    async fn run_pipeline_limited(
+        data_file: String,
+        out_dir: PathBuf,
+        query: BenchmarkQuery,
+        progress: Option<ProgressHook>,
+        run_id: &str,
+    ) -> Result<Result<HashMap<String, f64>, String>, TimeoutReport> {
+        run_limited(run_id, progress, move |hook, abort| {
+            // Make room for this run's directory
+            if let Some(keep) = keep_runs() {
+                prune_runs(keep.saturating_sub(1));
+            }
+            run_pipeline(&data_file, &out_dir, query, Some(hook), abort)
+        })
+        .await
+    }
+
+    /// Progress of the runs in flight (oldest first): current stage, elapsed time and the
+    /// finished stages' timings. `204 No Content` when nothing is running.
+    pub async fn current_run() -> Response {
+        let mut runs: Vec<LiveRun> = lock(live_runs())
+            .iter()
+            .map(|(run_id, tracker)| {
+                let t = lock(tracker);
+                LiveRun {
+                    run_id: run_id.clone(),
+                    stage: t.stage,
+                    elapsed_seconds: t.started.elapsed().as_secs_f64(),
+                    metrics: t.metrics.clone(),
+                }
+            })
+            .collect();
+        if runs.is_empty() {
+            return StatusCode::NO_CONTENT.into_response();
+        }
+        runs.sort_by(|a, b| b.elapsed_seconds.total_cmp(&a.elapsed_seconds));
+        Json(runs).into_response()
+    }
+
+    /// JSON response with the time spent encoding it in `x-serialization-time-ms`, so
+    /// clients can tell response building apart from the ETL timings in the body.
+    fn timed_json<T: Serialize>(value: &T) -> Response {
+        let start = Instant::now();
+        let Ok(body) = serde_json::to_vec(value) else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let ms = start.elapsed().as_secs_f64() * 1000.0;
+        let headers = [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (HeaderName::from_static("x-serialization-time-ms"), format!("{:.3}", ms)),
+        ];
+        (headers, body).into_response()
+    }
+
+    fn list_files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().is_file())
+                    .filter_map(|e| e.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files
     }
 
     pub async fn run_benchmark(
-        _query: Query<BenchmarkQuery>,
-    ) -> Result<Json<BenchmarkResult>, StatusCode> {
-        // Demo metrics (replace with real run if you want to execute ETL here)
+        Query(query): Query<BenchmarkQuery>,
+    ) -> Result<Response, Response> {
+        // Without the dataset on the host, fall back to the representative payload
+        let data_file = data_file();
+        if !input_exists(&data_file) {
+            return Ok(timed_json(&demo_result(query.label)));
+        }
+        if let Err(e) = query.stages() {
+            eprintln!("❌ Rejected /benchmark request: {}", e);
+            return Err(StatusCode::BAD_REQUEST.into_response());
+        }
+
+        let fingerprint = input_fingerprint(&data_file).ok();
+        let run_id = new_run_id();
+        let out_dir = runs_dir().join(&run_id);
+        let metrics = run_pipeline_limited(data_file, out_dir.clone(), query.clone(), None, &run_id)
+            .await
+            .map_err(|report| {
+                eprintln!("⏱️  Benchmark run {} timed out during {:?}", run_id, report.stage);
+                (StatusCode::GATEWAY_TIMEOUT, Json(report)).into_response()
+            })?
+            .map_err(|e| {
+                eprintln!("❌ Benchmark run {} failed: {}", run_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            })?;
+
+        let rows = metrics.get("rows_after_cleaning").copied().unwrap_or(0.0);
+        let total = metrics.get("total_time").copied().unwrap_or(0.0);
+        let mut performance_summary = format!(
+            "🚀 Polars processed {:.0} cleaned taxi records in {:.2}s ({:.0} records/second).",
+            rows,
+            total,
+            if total > 0.0 { rows / total } else { 0.0 }
+        );
+        // Only a full-dataset run is comparable with the pandas script's full run
+        if query.sample_size.is_none() && query.budget_seconds.is_none() {
+            if let Ok(pandas) = read_engine_run("pandas", &pandas_metrics_file()) {
+                let polars = EngineRun::from_metrics("polars", &metrics);
+                let comparison = ComparisonResult { polars, other: pandas };
+                performance_summary += " ";
+                performance_summary += &SpeedupNarrator::new().explain_speedup(&comparison);
+            }
+        }
+        let downloads = list_files(&out_dir)
+            .into_iter()
+            .map(|f| format!("/download/{}/{}", run_id, f))
+            .collect();
+
+        Ok(timed_json(&BenchmarkResult {
+            metrics,
+            message: "✅ Polars ETL benchmark completed successfully.".to_string(),
+            performance_summary,
+            dataset_info: dataset_info(),
+            label: query.label,
+            run_id: Some(run_id),
+            input_fingerprint: fingerprint,
+            downloads,
+        }))
+    }
+
+    /// Server-sent events for a live run: a `progress` event at every stage start and finish,
+    /// then a final `result`, `error` or `timeout` event.
+    pub async fn stream_benchmark(Query(query): Query<BenchmarkQuery>) -> Response {
+        let data_file = data_file();
+        if !input_exists(&data_file) {
+            return (StatusCode::SERVICE_UNAVAILABLE, "dataset not available on this host")
+                .into_response();
+        }
+        if let Err(e) = query.stages() {
+            return (StatusCode::BAD_REQUEST, e).into_response();
+        }
+
+        // (event, is_last)
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<(Event, bool)>();
+        let progress_tx = tx.clone();
+        let hook: ProgressHook = Arc::new(move |event: &ProgressEvent| {
+            if let Ok(event) = Event::default().event("progress").json_data(event) {
+                let _ = progress_tx.send((event, false));
+            }
+        });
+
+        let run_id = new_run_id();
+        let out_dir = runs_dir().join(&run_id);
+        tokio::spawn(async move {
+            let last = match run_pipeline_limited(data_file, out_dir, query, Some(hook), &run_id).await {
+                Ok(Ok(metrics)) => Event::default()
+                    .event("result")
+                    .json_data(serde_json::json!({ "run_id": run_id, "metrics": metrics })),
+                Ok(Err(e)) => Ok(Event::default().event("error").data(e)),
+                Err(report) => Event::default().event("timeout").json_data(report),
+            };
+            if let Ok(event) = last {
+                let _ = tx.send((event, true));
+            }
+        });
+
+        // The stream ends with the final event, even if a timed-out run is still winding down
+        let events = futures_util::stream::unfold(Some(rx), |rx| async move {
+            let mut rx = rx?;
+            let (event, last) = rx.recv().await?;
+            Some((Ok::<_, Infallible>(event), (!last).then_some(rx)))
+        });
+        Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+    }
+
+    // `504` with what the run got through before `MAX_RUN_SECONDS`
+    fn timed_out(route: &str, report: TimeoutReport) -> Response {
+        eprintln!("⏱️  {} run {} timed out during {:?}", route, report.run_id, report.stage);
+        (StatusCode::GATEWAY_TIMEOUT, Json(report)).into_response()
+    }
+
+    /// Load and clean (honouring `sample_size`), then run the daily aggregation under
+    /// Polars' profiler and return the per-operator timings. Capped and listed like `/benchmark`.
+    pub async fn plan_analyze(Query(query): Query<ResultRunQuery>) -> Response {
+        if query.result.is_some() {
+            return (StatusCode::BAD_REQUEST, "/plan/analyze always profiles the daily aggregation")
+                .into_response();
+        }
+        let data_file = data_file();
+        if !input_exists(&data_file) {
+            return (StatusCode::SERVICE_UNAVAILABLE, "dataset not available on this host")
+                .into_response();
+        }
+
+        let run_id = new_run_id();
+        let profiled = run_limited(&run_id, None, move |hook, abort| {
+            let mut etl = query.load_and_clean(&data_file, hook, abort)?;
+            check_abort(abort, "explain_analyze")?;
+            let operators = etl.explain_analyze().map_err(|e| e.to_string())?;
+            Ok((operators, etl.get_metrics().clone()))
+        })
+        .await;
+        match profiled {
+            Ok(Ok((operators, metrics))) => {
+                Json(serde_json::json!({ "operators": operators, "metrics": metrics })).into_response()
+            }
+            Ok(Err(e)) => {
+                eprintln!("❌ /plan/analyze failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+            }
+            Err(report) => timed_out("/plan/analyze", report),
+        }
+    }
+
+    /// Load, clean and compute one aggregated table, then send it as an Arrow IPC stream so
+    /// Arrow-aware clients can read it without going through JSON. Capped and listed like
+    /// `/benchmark`.
+    pub async fn arrow_result(Query(query): Query<ResultRunQuery>) -> Response {
+        let name = match query.result() {
+            Ok(name) => name,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+        let data_file = data_file();
+        if !input_exists(&data_file) {
+            return (StatusCode::SERVICE_UNAVAILABLE, "dataset not available on this host")
+                .into_response();
+        }
+
+        let run_id = new_run_id();
+        let encoded = run_limited(&run_id, None, move |hook, abort| {
+            let mut etl = query.load_and_clean(&data_file, hook, abort)?;
+            check_abort(abort, "aggregate")?;
+            etl.collect_result(&name).map_err(|e| e.to_string())?;
+            etl.result_ipc_stream(&name).map_err(|e| e.to_string())
+        })
+        .await;
+        match encoded {
+            Ok(Ok(Some(bytes))) => (
+                [(header::CONTENT_TYPE, "application/vnd.apache.arrow.stream")],
+                Body::from(bytes),
+            )
+                .into_response(),
+            Ok(Ok(None)) => StatusCode::NOT_FOUND.into_response(),
+            Ok(Err(e)) => {
+                eprintln!("❌ /results/arrow failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()
+            }
+            Err(report) => timed_out("/results/arrow", report),
+        }
+    }
+
+    // Rows per NDJSON chunk on /results/stream
+    #[cfg(feature = "streaming")]
+    const STREAM_CHUNK_ROWS: usize = 1024;
+
+    /// Load and clean, collect only the requested aggregation on Polars' streaming engine
+    /// (the input goes through in morsels, so memory is bounded by the group state rather
+    /// than the dataset), then send it as NDJSON in a chunked body, a chunk of rows at a time
+    /// through a small bounded channel. The last line is the run's metrics, including
+    /// `result_rows_streamed`. Load, clean and collect are capped and listed like `/benchmark`.
+    #[cfg(feature = "streaming")]
+    pub async fn stream_result(Query(query): Query<ResultRunQuery>) -> Response {
+        let name = match query.result() {
+            Ok(name) => name,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+        let data_file = data_file();
+        if !input_exists(&data_file) {
+            return (StatusCode::SERVICE_UNAVAILABLE, "dataset not available on this host")
+                .into_response();
+        }
+
+        // Failures up to here still get a proper status; after that the body is already going
+        let run_id = new_run_id();
+        let collect_name = name.clone();
+        let aggregated = run_limited(&run_id, None, move |hook, abort| {
+            let mut etl = query.load_and_clean(&data_file, hook, abort)?.with_streaming(true);
+            check_abort(abort, "aggregate")?;
+            etl.collect_result(&collect_name).map_err(|e| e.to_string())?;
+            Ok(etl)
+        })
+        .await;
+        let mut etl = match aggregated {
+            Ok(Ok(etl)) => etl,
+            Ok(Err(e)) => {
+                eprintln!("❌ /results/stream failed: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+            }
+            Err(report) => return timed_out("/results/stream", report),
+        };
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<Vec<u8>, std::io::Error>>(4);
+        tokio::task::spawn_blocking(move || {
+            let rows_tx = tx.clone();
+            let sent = etl.result_ndjson_chunks(&name, STREAM_CHUNK_ROWS, |chunk| {
+                rows_tx.blocking_send(Ok(chunk)).is_ok()
+            });
+            let last = match sent {
+                Ok(_) => {
+                    let mut line = etl.metrics_json().to_string().into_bytes();
+                    line.push(b'\n');
+                    Ok(line)
+                }
+                Err(e) => {
+                    eprintln!("❌ /results/stream failed: {}", e);
+                    Err(std::io::Error::other(e.to_string()))
+                }
+            };
+            let _ = tx.blocking_send(last);
+        });
+
+        let chunks = futures_util::stream::unfold(rx, |mut rx| async move {
+            let chunk = rx.recv().await?;
+            Some((chunk, rx))
+        });
+        (
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(chunks),
+        )
+            .into_response()
+    }
+
+    fn demo_result(label: Option<String>) -> BenchmarkResult {
+        // Demo metrics (served when the dataset is not available on the host)
         let mut metrics = HashMap::new();
         metrics.insert("load_time".to_string(), 1.2);
         metrics.insert("clean_time".to_string(), 0.8);
@@ -131,29 +1466,89 @@ mod shuttle_app {
         metrics.insert("long_trips_count".to_string(), 45_632.0);
         metrics.insert("expensive_trips_count".to_string(), 123_456.0);
 
-        let dataset_info = DatasetInfo {
-            name: "NYC Yellow Taxi Data (January 2015)".to_string(),
-            rows: 12_748_986,
-            size_mb: "~2.1 GB".to_string(),
-            columns: 19,
-        };
-
         let rows_per_second = 12_748_986.0 / 2.8;
         let performance_summary = format!(
-            "🚀 Polars processed {:.1}M taxi records in just {:.1}s - that's {:.0} records/second! \
-             This demonstrates Rust's superior performance for data-intensive workloads.",
+            "Demo figures (the dataset is not on this host): Polars processed {:.1}M taxi records in {:.1}s \
+             ({:.0} records/second).",
             12.7, 2.8, rows_per_second
         );
 
-        Ok(Json(BenchmarkResult {
+        BenchmarkResult {
             metrics,
             message: "✅ Polars ETL benchmark completed successfully with blazing speed!".to_string(),
             performance_summary,
-            dataset_info,
-        }))
+            dataset_info: dataset_info(),
+            label,
+            run_id: None,
+            input_fingerprint: None,
+            downloads: Vec::new(),
+        }
+    }
+
+    // Path components may only contain [A-Za-z0-9._-] and must not start with a dot,
+    // which rules out separators, `..` and hidden files.
+    fn is_safe_component(s: &str) -> bool {
+        !s.is_empty()
+            && !s.starts_with('.')
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    }
+
+    /// Resolve `run_id/filename` to a file inside the runs directory, or `None` if it
+    /// does not exist or would escape it (e.g. through a symlink).
+    fn resolve_download(root: &Path, run_id: &str, filename: &str) -> Option<PathBuf> {
+        if !is_safe_component(run_id) || !is_safe_component(filename) {
+            return None;
+        }
+        let root = root.canonicalize().ok()?;
+        let run_dir = root.join(run_id).canonicalize().ok()?;
+        let path = run_dir.join(filename).canonicalize().ok()?;
+        (run_dir.starts_with(&root) && path.starts_with(&run_dir) && path.is_file()).then_some(path)
+    }
+
+    fn content_type(filename: &str) -> &'static str {
+        match filename.rsplit('.').next() {
+            Some("csv") => "text/csv; charset=utf-8",
+            Some("json") => "application/json",
+            Some("parquet") => "application/vnd.apache.parquet",
+            Some("arrow") | Some("ipc") => "application/vnd.apache.arrow.file",
+            _ => "application/octet-stream",
+        }
+    }
+
+    pub async fn download(
+        RoutePath((run_id, filename)): RoutePath<(String, String)>,
+    ) -> Result<Response, StatusCode> {
+        let path = resolve_download(&runs_dir(), &run_id, &filename).ok_or(StatusCode::NOT_FOUND)?;
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|_| StatusCode::NOT_FOUND)?;
+
+        let headers = [
+            (header::CONTENT_TYPE, content_type(&filename).to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ];
+        Ok((headers, Body::from_stream(ReaderStream::new(file))).into_response())
     }
 
     pub async fn health_check() -> Json<HealthResponse> {
+        #[allow(unused_mut)]
+        let mut endpoints = vec![
+            "GET /".to_string(),
+            "GET /favicon.ico".to_string(),
+            "GET /health".to_string(),
+            "GET /benchmark".to_string(),
+            "GET /benchmark?sample_size=1000".to_string(),
+            "GET /benchmark/stream".to_string(),
+            "GET /benchmark/current".to_string(),
+            "GET /plan/analyze".to_string(),
+            "GET /results/arrow?result=daily".to_string(),
+            "GET /download/{run_id}/{filename}".to_string(),
+        ];
+        #[cfg(feature = "streaming")]
+        endpoints.push("GET /results/stream?result=daily".to_string());
         Json(HealthResponse {
             status: "healthy".to_string(),
             service: "Polars ETL Benchmark API".to_string(),
@@ -161,15 +1556,43 @@ mod shuttle_app {
             description:
                 "High-performance data processing with Rust and Polars - showcasing 5-8x speedup over Python Pandas"
                     .to_string(),
-            endpoints: vec![
-                "GET /".to_string(),
-                "GET /health".to_string(),
-                "GET /benchmark".to_string(),
-                "GET /benchmark?sample_size=1000".to_string(),
-            ],
+            endpoints,
+            supported_formats: OutputFormat::supported()
+                .into_iter()
+                .map(|f| f.name().to_string())
+                .collect(),
         })
     }
 
+    /// True when the `Accept` header ranks `text/html` ahead of `application/json`.
+    /// Missing headers and `*/*` fall through to JSON so API clients keep working.
+    fn prefers_html(headers: &HeaderMap) -> bool {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+
+        let html = accept.find("text/html");
+        let json = accept.find("application/json");
+        match (html, json) {
+            (Some(h), Some(j)) => h < j,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+
+    pub async fn root(headers: HeaderMap) -> Response {
+        if prefers_html(&headers) {
+            Html(LANDING_PAGE).into_response()
+        } else {
+            health_check().await.into_response()
+        }
+    }
+
+    pub async fn favicon() -> Response {
+        ([(header::CONTENT_TYPE, "image/svg+xml")], FAVICON_SVG).into_response()
+    }
+
     pub async fn get_comparison_info() -> Json<serde_json::Value> {
         Json(serde_json::json!({
             "benchmark_info": {
@@ -215,14 +1638,27 @@ mod shuttle_app {
 #[shuttle_runtime::main]
 async fn main() -> shuttle_axum::ShuttleAxum {
     use axum::{routing::get, Router};
+    use polars_etl_benchmark::etl::OutputFormat;
     use tower_http::cors::CorsLayer;
 
+    let formats: Vec<&str> = OutputFormat::supported().into_iter().map(|f| f.name()).collect();
+    println!("📦 Supported output formats: {}", formats.join(", "));
+
     let router = Router::new()
-        .route("/", get(shuttle_app::health_check))
+        .route("/", get(shuttle_app::root))
+        .route("/favicon.ico", get(shuttle_app::favicon))
         .route("/health", get(shuttle_app::health_check))
         .route("/benchmark", get(shuttle_app::run_benchmark))
+        .route("/benchmark/stream", get(shuttle_app::stream_benchmark))
+        .route("/benchmark/current", get(shuttle_app::current_run))
+        .route("/plan/analyze", get(shuttle_app::plan_analyze))
+        .route("/results/arrow", get(shuttle_app::arrow_result))
         .route("/info", get(shuttle_app::get_comparison_info))
-        .layer(CorsLayer::permissive());
+        .route("/download/{run_id}/{filename}", get(shuttle_app::download));
+    // Only with the streaming engine compiled in; without it the endpoint would buffer
+    #[cfg(feature = "streaming")]
+    let router = router.route("/results/stream", get(shuttle_app::stream_result));
+    let router = router.layer(CorsLayer::permissive());
 
     Ok(router.into())
 }
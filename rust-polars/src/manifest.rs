@@ -0,0 +1,270 @@
+use crate::etl::{OptimizationFlags, PolarsETL, Stage};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+// ------- run manifest: everything needed to re-run a benchmark and check it reproduces -------
+
+/// Environment variables that cap the Polars/Rayon thread pools.
+pub const THREAD_ENV: [&str; 2] = ["POLARS_MAX_THREADS", "RAYON_NUM_THREADS"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub created_at: String,
+    /// CLI arguments of the run, re-parsed on replay.
+    pub args: Vec<String>,
+    /// Thread caps that were set (see [`THREAD_ENV`]).
+    pub env: BTreeMap<String, String>,
+    pub available_parallelism: usize,
+    /// Polars optimizer passes the pipeline collected with.
+    #[serde(default)]
+    pub optimizations: OptimizationFlags,
+    pub metrics: BTreeMap<String, f64>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn capture(args: Vec<String>, etl: &PolarsETL) -> Self {
+        Manifest {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            args,
+            env: THREAD_ENV
+                .iter()
+                .filter_map(|k| std::env::var(k).ok().map(|v| (k.to_string(), v)))
+                .collect(),
+            available_parallelism: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            optimizations: etl.optimizations(),
+            metrics: etl.get_metrics().iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            tags: etl.get_tags().iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        }
+    }
+
+    pub fn write(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn read(path: &str) -> std::io::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// One metric recorded in a manifest (or baseline) next to its value in a replay (or the
+/// current run).
+#[derive(Debug)]
+pub struct MetricDrift {
+    pub metric: String,
+    pub recorded: f64,
+    pub replayed: f64,
+}
+
+impl MetricDrift {
+    /// Timings and memory vary run to run; everything else (counts, rows, flags) is a result.
+    pub fn is_measurement(&self) -> bool {
+        self.metric.contains("time")
+            || self.metric.contains("memory")
+            || self.metric.ends_with("_ratio")
+            || self.metric.ends_with("_ms")
+            || self.metric == "machine_score"
+    }
+
+    pub fn change_pct(&self) -> f64 {
+        if self.recorded == 0.0 {
+            if self.replayed == 0.0 { 0.0 } else { f64::INFINITY }
+        } else {
+            (self.replayed - self.recorded) / self.recorded.abs() * 100.0
+        }
+    }
+}
+
+/// Metrics present in both runs, sorted by name.
+pub fn diff_metrics(recorded: &BTreeMap<String, f64>, replayed: &HashMap<String, f64>) -> Vec<MetricDrift> {
+    recorded
+        .iter()
+        .filter_map(|(k, &recorded)| {
+            replayed.get(k).map(|&replayed| MetricDrift { metric: k.clone(), recorded, replayed })
+        })
+        .collect()
+}
+
+// ------- regression guard: stage timings against a previous run -------
+
+/// A previous run to check a new one against: its numeric metrics and its text tags
+/// (`label`, `input_fingerprint`, ...).
+#[derive(Debug, Default)]
+pub struct Baseline {
+    pub path: String,
+    pub metrics: BTreeMap<String, f64>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl Baseline {
+    pub fn label(&self) -> Option<&str> {
+        self.tags.get("label").map(String::as_str)
+    }
+
+    // A `benchmark_manifest.json` (its `metrics` and `tags`) or a flat metrics JSON such as
+    // `polars_metrics.json` (numbers are metrics, strings are tags)
+    fn read_file(path: &Path) -> Result<Self, String> {
+        let shown = path.display();
+        let json = std::fs::read_to_string(path).map_err(|e| format!("cannot read baseline {}: {}", shown, e))?;
+        let value: serde_json::Value =
+            serde_json::from_str(&json).map_err(|e| format!("invalid baseline {}: {}", shown, e))?;
+        let object = |v: &serde_json::Value| {
+            v.as_object().cloned().ok_or_else(|| format!("baseline {} is not a metrics object", shown))
+        };
+        let (metrics, tags) = match value.get("metrics") {
+            Some(metrics) => (object(metrics)?, value.get("tags").map(object).transpose()?.unwrap_or_default()),
+            None => (object(&value)?, object(&value)?),
+        };
+        Ok(Baseline {
+            path: shown.to_string(),
+            metrics: metrics.iter().filter_map(|(k, v)| v.as_f64().map(|v| (k.clone(), v))).collect(),
+            tags: tags.iter().filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string()))).collect(),
+        })
+    }
+}
+
+/// The run to check against. `path` is one run's manifest or metrics JSON, or a directory of
+/// runs (its `*.json` files and those one level down, e.g. `../results/runs`), from which
+/// the most recently written one is taken. With `label`, only a run carrying that `label`
+/// qualifies: a single file with another label is an error, and in a directory the others
+/// are passed over.
+pub fn read_baseline(path: &str, label: Option<&str>) -> Result<Baseline, String> {
+    let root = Path::new(path);
+    if !root.is_dir() {
+        let baseline = Baseline::read_file(root)?;
+        if let Some(wanted) = label.filter(|wanted| baseline.label() != Some(*wanted)) {
+            return Err(format!(
+                "baseline {} is labelled {}, not '{}'",
+                path,
+                baseline.label().map_or("nothing".to_string(), |l| format!("'{}'", l)),
+                wanted
+            ));
+        }
+        return Ok(baseline);
+    }
+
+    let json_files = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "json"))
+            .collect()
+    };
+    let mut candidates = json_files(root);
+    for entry in std::fs::read_dir(root).map_err(|e| format!("cannot read baseline {}: {}", path, e))? {
+        let sub = entry.map_err(|e| format!("cannot read baseline {}: {}", path, e))?.path();
+        if sub.is_dir() {
+            candidates.extend(json_files(&sub));
+        }
+    }
+    // Newest first; files that aren't runs (no stage timings) or carry another label are skipped
+    candidates.sort_by_key(|p| std::cmp::Reverse(p.metadata().and_then(|m| m.modified()).ok()));
+    candidates
+        .iter()
+        .filter_map(|p| Baseline::read_file(p).ok())
+        .filter(|b| b.metrics.keys().any(|k| k.ends_with("_time")))
+        .find(|b| label.is_none() || b.label() == label)
+        .ok_or_else(|| match label {
+            Some(label) => format!("no run labelled '{}' under {}", label, path),
+            None => format!("no run under {}", path),
+        })
+}
+
+/// Stages faster than this in both runs always pass: at that size the change is timer noise.
+pub const REGRESSION_FLOOR_SECS: f64 = 0.05;
+
+/// How far a stage slowed down against the `--fail-on-regression` limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegressionLevel {
+    Pass,
+    Warn, // slower by more than half the limit
+    Fail, // slower by more than the limit
+}
+
+/// `<stage>_time` of every pipeline stage recorded in both runs, in pipeline order, rated
+/// against `limit_pct` (pass `f64::INFINITY` to only report the changes). Stages under
+/// [`REGRESSION_FLOOR_SECS`] in both runs pass whatever the change.
+pub fn stage_regressions(
+    baseline: &BTreeMap<String, f64>,
+    current: &HashMap<String, f64>,
+    limit_pct: f64,
+) -> Vec<(MetricDrift, RegressionLevel)> {
+    Stage::ALL
+        .iter()
+        .filter_map(|stage| {
+            let metric = format!("{}_time", stage.name());
+            let drift = MetricDrift {
+                recorded: *baseline.get(&metric)?,
+                replayed: *current.get(&metric)?,
+                metric,
+            };
+            let change = drift.change_pct();
+            let level = if drift.recorded.max(drift.replayed) < REGRESSION_FLOOR_SECS {
+                RegressionLevel::Pass
+            } else if change > limit_pct {
+                RegressionLevel::Fail
+            } else if change > limit_pct / 2.0 {
+                RegressionLevel::Warn
+            } else {
+                RegressionLevel::Pass
+            };
+            Some((drift, level))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(recorded: f64, current: f64, limit_pct: f64) -> Option<RegressionLevel> {
+        let baseline = BTreeMap::from([("load_time".to_string(), recorded)]);
+        let current = HashMap::from([("load_time".to_string(), current)]);
+        stage_regressions(&baseline, &current, limit_pct).first().map(|(_, level)| *level)
+    }
+
+    #[test]
+    fn slowdowns_are_rated_against_the_limit() {
+        assert_eq!(level(1.0, 1.05, 20.0), Some(RegressionLevel::Pass));
+        assert_eq!(level(1.0, 1.15, 20.0), Some(RegressionLevel::Warn));
+        assert_eq!(level(1.0, 1.30, 20.0), Some(RegressionLevel::Fail));
+    }
+
+    #[test]
+    fn stages_under_the_floor_pass() {
+        assert_eq!(level(0.010, 0.040, 20.0), Some(RegressionLevel::Pass));
+        // Crossing the floor is still a regression
+        assert_eq!(level(0.010, 0.200, 20.0), Some(RegressionLevel::Fail));
+    }
+
+    #[test]
+    fn stages_missing_from_either_run_are_skipped() {
+        let baseline = BTreeMap::from([("load_time".to_string(), 1.0)]);
+        let current = HashMap::from([("clean_time".to_string(), 1.0)]);
+        assert!(stage_regressions(&baseline, &current, 20.0).is_empty());
+    }
+
+    #[test]
+    fn baseline_directory_is_filtered_by_label() {
+        let dir = std::env::temp_dir().join(format!("baseline_label_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("run-b")).unwrap();
+        std::fs::write(dir.join("run-a.json"), r#"{"load_time": 1.0, "label": "laptop"}"#).unwrap();
+        std::fs::write(dir.join("run-b/polars_metrics.json"), r#"{"load_time": 2.0, "label": "ci"}"#).unwrap();
+        std::fs::write(dir.join("notes.json"), r#"{"label": "ci"}"#).unwrap(); // not a run
+        let path = dir.to_str().unwrap();
+
+        let ci = read_baseline(path, Some("ci")).unwrap();
+        assert_eq!(ci.metrics["load_time"], 2.0);
+        let laptop = read_baseline(path, Some("laptop")).unwrap();
+        assert_eq!(laptop.metrics["load_time"], 1.0);
+        assert!(read_baseline(path, Some("server")).is_err());
+
+        // A single file has to carry the label asked for
+        let file = dir.join("run-a.json");
+        assert!(read_baseline(file.to_str().unwrap(), Some("ci")).is_err());
+        assert_eq!(read_baseline(file.to_str().unwrap(), None).unwrap().label(), Some("laptop"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use crate::etl::OutputFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------- benchmark matrix: one pipeline run per combination of the axes (`matrix`) -------
+
+/// `matrix.toml`: the axes to sweep, the input and where the combined records go.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixConfig {
+    /// Input of every cell (default ../data/yellow_tripdata_2015-01.csv).
+    pub input: Option<String>,
+    /// JSON Lines file with one record per cell.
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// Extra CLI options for every cell, e.g. `["--stages", "load,clean,aggregate"]`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub axes: MatrixAxes,
+}
+
+fn default_output() -> String {
+    "../results/matrix_results.jsonl".into()
+}
+
+/// An empty (or missing) axis leaves that parameter at the pipeline's default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixAxes {
+    /// Thread caps (`POLARS_MAX_THREADS` / `RAYON_NUM_THREADS`).
+    #[serde(default)]
+    pub threads: Vec<usize>,
+    /// Rows read from the input; 0 = all of it.
+    #[serde(default)]
+    pub sample_sizes: Vec<usize>,
+    /// Result file formats (csv|parquet|arrow).
+    #[serde(default)]
+    pub formats: Vec<String>,
+}
+
+impl MatrixConfig {
+    /// Parse and check a matrix config: thread counts above 0 and formats in this build.
+    pub fn read(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        let config: MatrixConfig = toml::from_str(&text).map_err(|e| format!("invalid {}: {}", path, e))?;
+        if config.axes.threads.contains(&0) {
+            return Err(format!("{}: threads must be at least 1", path));
+        }
+        for format in &config.axes.formats {
+            let parsed: OutputFormat = format.parse()?;
+            parsed.ensure_supported().map_err(|e| e.to_string())?;
+        }
+        Ok(config)
+    }
+
+    /// Every combination of the axes, threads outermost, then sample sizes, then formats.
+    pub fn cells(&self) -> Vec<MatrixCell> {
+        fn axis<T: Clone>(values: &[T]) -> Vec<Option<T>> {
+            if values.is_empty() {
+                vec![None]
+            } else {
+                values.iter().cloned().map(Some).collect()
+            }
+        }
+
+        let mut cells = Vec::new();
+        for threads in axis(&self.axes.threads) {
+            for sample_size in axis(&self.axes.sample_sizes) {
+                for format in axis(&self.axes.formats) {
+                    cells.push(MatrixCell {
+                        threads,
+                        sample_size: sample_size.filter(|n| *n > 0),
+                        format: format.map(|f| f.to_ascii_lowercase()),
+                    });
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// One combination of the axes; None = the pipeline's default for that parameter.
+#[derive(Clone, Debug, Serialize)]
+pub struct MatrixCell {
+    pub threads: Option<usize>,
+    pub sample_size: Option<usize>,
+    pub format: Option<String>,
+}
+
+impl MatrixCell {
+    /// "threads=4 sample_size=100000 format=parquet"; parameters left at the default are omitted.
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(n) = self.threads {
+            parts.push(format!("threads={}", n));
+        }
+        if let Some(n) = self.sample_size {
+            parts.push(format!("sample_size={}", n));
+        }
+        if let Some(format) = &self.format {
+            parts.push(format!("format={}", format));
+        }
+        if parts.is_empty() {
+            "defaults".into()
+        } else {
+            parts.join(" ")
+        }
+    }
+
+    /// Pipeline arguments for this cell. The thread caps go in the environment instead,
+    /// since Polars reads them once when it builds its thread pool.
+    pub fn args(&self, input: &str) -> Vec<String> {
+        let mut args = vec!["--input".into(), input.into(), "--label".into(), format!("matrix {}", self.label())];
+        if let Some(n) = self.sample_size {
+            args.extend(["--sample-size".into(), n.to_string()]);
+        }
+        if let Some(format) = &self.format {
+            args.extend(["--output-format".into(), format.clone()]);
+        }
+        args
+    }
+}
+
+/// One line of the combined results: the cell's parameters and its metrics side by side, so
+/// the file loads straight into a table for plotting.
+#[derive(Debug, Serialize)]
+pub struct MatrixRecord {
+    #[serde(flatten)]
+    pub cell: MatrixCell,
+    pub error: Option<String>, // why the cell has no metrics
+    #[serde(flatten)]
+    pub metrics: BTreeMap<String, f64>,
+}
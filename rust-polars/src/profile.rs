@@ -0,0 +1,58 @@
+use crate::etl::PolarsETL;
+use crate::status;
+use polars::prelude::*;
+use serde::Serialize;
+use std::time::Instant;
+
+// ------- explain analyze: per-operator timings from Polars' profiler -------
+
+/// Wall time of one node of the executed plan, relative to the start of the query.
+#[derive(Clone, Debug, Serialize)]
+pub struct OperatorTiming {
+    pub node: String,
+    pub start_us: u64,
+    pub end_us: u64,
+    pub duration_ms: f64,
+}
+
+impl PolarsETL {
+    /// Run the daily aggregation on the current (cleaned) plan with profiling on and
+    /// return each operator's timings in execution order. Records `explain_analyze_time`
+    /// and `explain_analyze_operators`. Call after `clean_data`.
+    pub fn explain_analyze(&mut self) -> PolarsResult<Vec<OperatorTiming>> {
+        let Some(df) = self.df.clone() else {
+            return Ok(Vec::new());
+        };
+        status!("Profiling the daily aggregation...");
+        let start = Instant::now();
+
+        let feats = df.with_columns([col("tpep_pickup_datetime").dt().date().alias("date")]);
+        let (_, profile) = self.optimized(self.daily_plan(feats)).profile()?;
+
+        let nodes = profile.column("node")?.str()?;
+        let starts = profile.column("start")?.cast(&DataType::UInt64)?;
+        let ends = profile.column("end")?.cast(&DataType::UInt64)?;
+        let (starts, ends) = (starts.u64()?, ends.u64()?);
+        let mut timings: Vec<OperatorTiming> = (0..profile.height())
+            .map(|i| {
+                let (start_us, end_us) = (starts.get(i).unwrap_or(0), ends.get(i).unwrap_or(0));
+                OperatorTiming {
+                    node: nodes.get(i).unwrap_or_default().to_string(),
+                    start_us,
+                    end_us,
+                    duration_ms: end_us.saturating_sub(start_us) as f64 / 1000.0,
+                }
+            })
+            .collect();
+        timings.sort_by_key(|t| t.start_us);
+
+        let t = start.elapsed().as_secs_f64();
+        self.metrics.insert("explain_analyze_time".into(), t);
+        self.metrics.insert("explain_analyze_operators".into(), timings.len() as f64);
+        for timing in &timings {
+            status!("  {:<40} {:>10.2}ms", timing.node, timing.duration_ms);
+        }
+        status!("✅ Profiled {} operators in {:.2}s", timings.len(), t);
+        Ok(timings)
+    }
+}
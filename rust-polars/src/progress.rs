@@ -0,0 +1,12 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+// ------- progress reporting -------
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    StageStarted { stage: &'static str },
+    StageFinished { stage: &'static str, seconds: f64 },
+}
+
+pub type ProgressHook = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
@@ -0,0 +1,280 @@
+use crate::etl::PolarsETL;
+use crate::status;
+use chrono::{NaiveDate, NaiveDateTime};
+use polars::prelude::*;
+use std::time::{Duration, Instant};
+
+// ------- named queries -------
+// Self-contained workloads run against the cleaned plan, selectable by name from
+// the CLI. Each one records `<name>_time` and `<name>_rows`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamedQuery {
+    /// Chain of selective filters narrowing the month down to a few thousand trips.
+    FilterStress,
+    /// Trips and revenue per pickup hour of day.
+    HourlyRevenue,
+    /// Trips, average fare and average tip per payment type.
+    PaymentSplit,
+    /// The busiest pickup cells (0.01° lon/lat boxes) by trip count.
+    TopPickupCells,
+}
+
+// Cells in the `top_pickup_cells` ranking
+const TOP_CELLS: u32 = 10;
+
+impl NamedQuery {
+    pub const ALL: [NamedQuery; 4] = [
+        NamedQuery::FilterStress,
+        NamedQuery::HourlyRevenue,
+        NamedQuery::PaymentSplit,
+        NamedQuery::TopPickupCells,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedQuery::FilterStress => "filter_stress",
+            NamedQuery::HourlyRevenue => "hourly_revenue",
+            NamedQuery::PaymentSplit => "payment_split",
+            NamedQuery::TopPickupCells => "top_pickup_cells",
+        }
+    }
+
+    /// Cleaned columns the query reads beyond the ones every load has.
+    pub fn optional_columns(self) -> &'static [&'static str] {
+        match self {
+            NamedQuery::FilterStress => &["fare_amount"],
+            NamedQuery::PaymentSplit => &["payment_type", "fare_amount", "tip_amount"],
+            NamedQuery::HourlyRevenue | NamedQuery::TopPickupCells => &[],
+        }
+    }
+
+    /// Build the query on top of the cleaned plan.
+    pub fn plan(self, cleaned: LazyFrame) -> LazyFrame {
+        match self {
+            NamedQuery::FilterStress => filter_stress_steps()
+                .into_iter()
+                .fold(cleaned, |lf, (_, predicate)| lf.filter(predicate)),
+            NamedQuery::HourlyRevenue => cleaned
+                .group_by([col("tpep_pickup_datetime").dt().hour().alias("hour")])
+                .agg([len().alias("trip_count"), col("total_amount").sum().alias("revenue")])
+                .sort(["hour"], Default::default()),
+            NamedQuery::PaymentSplit => cleaned
+                .group_by([col("payment_type")])
+                .agg([
+                    len().alias("trip_count"),
+                    col("fare_amount").mean().alias("avg_fare"),
+                    col("tip_amount").mean().alias("avg_tip"),
+                ])
+                .sort(["payment_type"], Default::default()),
+            NamedQuery::TopPickupCells => {
+                let cell = |c: &str| (col(c) * lit(100.0)).floor() / lit(100.0);
+                cleaned
+                    .filter(col("pickup_longitude").is_not_null().and(col("pickup_latitude").is_not_null()))
+                    .group_by([cell("pickup_longitude").alias("cell_lon"), cell("pickup_latitude").alias("cell_lat")])
+                    .agg([len().alias("trip_count")])
+                    .sort(
+                        ["trip_count"],
+                        SortMultipleOptions::default().with_order_descending(true),
+                    )
+                    .limit(TOP_CELLS)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for NamedQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NamedQuery::ALL
+            .into_iter()
+            .find(|q| q.name() == s)
+            .ok_or_else(|| format!("unknown query '{}'", s))
+    }
+}
+
+// Pickup "zones" as lon/lat boxes; the 2015 schema predates TLC zone ids
+const ZONES: [(&str, [f64; 4]); 3] = [
+    // name, [min_lon, max_lon, min_lat, max_lat]
+    ("midtown", [-74.000, -73.970, 40.740, 40.770]),
+    ("financial_district", [-74.020, -74.000, 40.700, 40.715]),
+    ("jfk", [-73.820, -73.770, 40.630, 40.665]),
+];
+
+fn at(y: i32, m: u32, d: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, d)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid date")
+}
+
+/// The `filter_stress` predicates in application order.
+pub fn filter_stress_steps() -> Vec<(&'static str, Expr)> {
+    let pickup = || col("tpep_pickup_datetime");
+    let in_zone = ZONES
+        .iter()
+        .map(|(_, [lon0, lon1, lat0, lat1])| {
+            col("pickup_longitude").gt_eq(lit(*lon0))
+                .and(col("pickup_longitude").lt(lit(*lon1)))
+                .and(col("pickup_latitude").gt_eq(lit(*lat0)))
+                .and(col("pickup_latitude").lt(lit(*lat1)))
+        })
+        .reduce(|a, b| a.or(b))
+        .expect("at least one zone");
+
+    vec![
+        (
+            "date_range",
+            pickup().gt_eq(lit(at(2015, 1, 5))).and(pickup().lt(lit(at(2015, 1, 12)))),
+        ),
+        (
+            "fare_bounds",
+            col("fare_amount").gt_eq(lit(10.0)).and(col("fare_amount").lt(lit(40.0))),
+        ),
+        (
+            "distance_bounds",
+            col("trip_distance").gt_eq(lit(2.0)).and(col("trip_distance").lt(lit(8.0))),
+        ),
+        (
+            "passenger_bounds",
+            col("passenger_count").gt_eq(lit(1)).and(col("passenger_count").lt_eq(lit(2))),
+        ),
+        ("zone_list", in_zone),
+    ]
+}
+
+impl PolarsETL {
+    /// Run a named query against the cleaned plan (call after `clean_data`).
+    pub fn run_named_query(&mut self, query: NamedQuery) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else {
+            return Ok(self);
+        };
+        let name = query.name();
+        status!("Running query {}...", name);
+
+        if query == NamedQuery::FilterStress {
+            // Survivors after each cumulative filter, counted in a single pass
+            let mut acc: Option<Expr> = None;
+            let counts: Vec<Expr> = filter_stress_steps()
+                .into_iter()
+                .enumerate()
+                .map(|(i, (_, predicate))| {
+                    let cumulative = match acc.take() {
+                        Some(prev) => prev.and(predicate),
+                        None => predicate,
+                    };
+                    acc = Some(cumulative.clone());
+                    cumulative
+                        .cast(DataType::Int64)
+                        .sum()
+                        .alias(format!("rows_after_filter_{}", i + 1))
+                })
+                .collect();
+            let counts = self.optimized(df.clone().select(counts)).collect()?;
+            for c in counts.get_columns() {
+                let n = c.i64()?.get(0).unwrap_or(0);
+                self.metrics.insert(c.name().to_string(), n as f64);
+            }
+        }
+
+        let start = Instant::now();
+        let out = self.optimized(query.plan(df)).collect()?;
+        let t = start.elapsed().as_secs_f64();
+
+        self.metrics.insert(format!("{}_time", name), t);
+        self.metrics.insert(format!("{}_rows", name), out.height() as f64);
+        status!("✅ {}: {} rows in {:.3}s", name, out.height(), t);
+        Ok(self)
+    }
+
+    /// Dashboard-style load test: materialize the cleaned frame once, then keep
+    /// `concurrency` workers firing the query `mix` (round robin, each worker starting at a
+    /// different query) against it for `duration`. Queries whose columns the frame lacks are
+    /// left out. Records `concurrent_query_kinds`, `concurrent_queries`, `concurrent_qps`,
+    /// `concurrent_p50_ms` and `concurrent_p99_ms`. Call after `clean_data`.
+    pub fn bench_concurrent_queries(
+        &mut self,
+        mix: Vec<NamedQuery>,
+        concurrency: usize,
+        duration: Duration,
+    ) -> PolarsResult<&mut Self> {
+        let Some(df) = self.df.clone() else {
+            return Ok(self);
+        };
+        if mix.is_empty() {
+            return Ok(self);
+        }
+        let workers = concurrency.max(1);
+        let names: Vec<&str> = mix.iter().map(|q| q.name()).collect();
+        status!(
+            "Running {} with {} workers for {:.0}s...",
+            names.join(", "),
+            workers,
+            duration.as_secs_f64()
+        );
+
+        let frame = df.collect()?;
+        // A dashboard over an older file just goes without the panels it has no columns for
+        let (mix, skipped): (Vec<NamedQuery>, Vec<NamedQuery>) = mix
+            .into_iter()
+            .partition(|q| q.optional_columns().iter().all(|c| frame.column(c).is_ok()));
+        if !skipped.is_empty() {
+            let names: Vec<&str> = skipped.iter().map(|q| q.name()).collect();
+            status!("⚠️  Skipping {} (columns not loaded)", names.join(", "));
+        }
+        if mix.is_empty() {
+            return Ok(self);
+        }
+        self.metrics.insert("concurrent_query_kinds".into(), mix.len() as f64);
+        let start = Instant::now();
+        let deadline = start + duration;
+        let latencies = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..workers)
+                .map(|w| {
+                    let (frame, mix) = (&frame, &mix);
+                    s.spawn(move || -> PolarsResult<Vec<f64>> {
+                        let mut times = Vec::new();
+                        for query in mix.iter().cycle().skip(w % mix.len()) {
+                            if Instant::now() >= deadline {
+                                break;
+                            }
+                            let t = Instant::now();
+                            query.plan(frame.clone().lazy()).collect()?;
+                            times.push(t.elapsed().as_secs_f64() * 1000.0);
+                        }
+                        Ok(times)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("query worker panicked"))
+                .collect::<PolarsResult<Vec<_>>>()
+        })?;
+        let elapsed = start.elapsed().as_secs_f64();
+
+        let mut latencies: Vec<f64> = latencies.into_iter().flatten().collect();
+        latencies.sort_by(f64::total_cmp);
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p * latencies.len() as f64).ceil() as usize;
+            latencies.get(rank.saturating_sub(1)).copied().unwrap_or(0.0)
+        };
+        let (p50, p99) = (percentile(0.50), percentile(0.99));
+        let qps = latencies.len() as f64 / elapsed.max(f64::EPSILON);
+
+        self.metrics.insert("concurrent_workers".into(), workers as f64);
+        self.metrics.insert("concurrent_queries".into(), latencies.len() as f64);
+        self.metrics.insert("concurrent_qps".into(), qps);
+        self.metrics.insert("concurrent_p50_ms".into(), p50);
+        self.metrics.insert("concurrent_p99_ms".into(), p99);
+        status!(
+            "✅ {} queries in {:.1}s: {:.1} queries/s, p50 {:.1}ms, p99 {:.1}ms",
+            latencies.len(),
+            elapsed,
+            qps,
+            p50,
+            p99
+        );
+        Ok(self)
+    }
+}
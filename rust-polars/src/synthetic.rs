@@ -0,0 +1,144 @@
+use chrono::{Duration, NaiveDate};
+use polars::prelude::*;
+
+// ------- synthetic NYC yellow-taxi data (same columns as the 2015 CSVs) -------
+
+// SplitMix64: tiny, seedable and good enough for fake trips; keeps `rand` out of the deps
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.unit()
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        self.unit() < p
+    }
+}
+
+fn cents(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
+
+/// Generate `rows` fake January 2015 trips, deterministic for a given `seed`.
+///
+/// Pickups are spread over the month with a rush-hour bias, distances are skewed short,
+/// durations follow distance at city speeds and fares follow the 2015 meter (plus tips on
+/// card payments, occasional tolls). About 1% of rows are the dirty records the cleaning
+/// step exists for: zero coordinates, out-of-range distances, missing passenger counts
+/// and dropoffs before pickups.
+pub fn generate_synthetic(rows: usize, seed: u64) -> PolarsResult<DataFrame> {
+    let mut rng = Rng(seed);
+    let month_start = NaiveDate::from_ymd_opt(2015, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid date");
+
+    let mut vendor = Vec::with_capacity(rows);
+    let mut pickup = Vec::with_capacity(rows);
+    let mut dropoff = Vec::with_capacity(rows);
+    let mut passengers: Vec<Option<i64>> = Vec::with_capacity(rows);
+    let mut distance = Vec::with_capacity(rows);
+    let mut pickup_lon = Vec::with_capacity(rows);
+    let mut pickup_lat = Vec::with_capacity(rows);
+    let mut dropoff_lon = Vec::with_capacity(rows);
+    let mut dropoff_lat = Vec::with_capacity(rows);
+    let mut payment = Vec::with_capacity(rows);
+    let mut fare = Vec::with_capacity(rows);
+    let mut extra = Vec::with_capacity(rows);
+    let mut tip = Vec::with_capacity(rows);
+    let mut tolls = Vec::with_capacity(rows);
+    let mut total = Vec::with_capacity(rows);
+
+    for _ in 0..rows {
+        // day of month, then an hour biased towards the 7-10 and 16-20 rush windows
+        let day = (rng.unit() * 31.0) as i64;
+        let hour = if rng.chance(0.4) {
+            if rng.chance(0.5) { rng.range(7.0, 10.0) } else { rng.range(16.0, 20.0) }
+        } else {
+            rng.range(0.0, 24.0)
+        };
+        let start = month_start + Duration::days(day) + Duration::seconds((hour * 3600.0) as i64);
+
+        // short trips dominate: exponential with a 2.5 mi mean, capped at 40 mi
+        let mut dist = cents((-rng.unit().max(1e-12).ln() * 2.5).clamp(0.1, 40.0));
+        let mph = rng.range(7.0, 22.0);
+        let mut minutes = dist / mph * 60.0 + rng.range(1.0, 6.0);
+
+        let (lon, lat) = (rng.range(-74.02, -73.93), rng.range(40.70, 40.82));
+        let bearing = rng.range(0.0, std::f64::consts::TAU);
+        let span = dist / 69.0; // ~69 miles per degree
+        let (mut lon2, lat2) = (lon + span * bearing.cos(), lat + span * bearing.sin());
+        let mut count = Some(if rng.chance(0.7) { 1 } else if rng.chance(0.5) { 2 } else { rng.range(3.0, 7.0) as i64 });
+
+        // dirty records
+        if rng.chance(0.004) {
+            lon2 = 0.0;
+        }
+        if rng.chance(0.003) {
+            dist = if rng.chance(0.5) { 0.0 } else { cents(rng.range(100.0, 300.0)) };
+        }
+        if rng.chance(0.002) {
+            count = None;
+        }
+        if rng.chance(0.001) {
+            minutes = -minutes;
+        }
+
+        let meter = cents(2.5 + 2.0 * dist + 0.35 * minutes.abs());
+        let surcharge = if (20.0..24.0).contains(&hour) { 0.5 } else if (16.0..20.0).contains(&hour) { 1.0 } else { 0.0 };
+        let card = rng.chance(0.6);
+        let tip_amount = if card { cents(meter * rng.range(0.1, 0.25)) } else { 0.0 };
+        let toll = if dist > 8.0 && rng.chance(0.3) { 5.33 } else { 0.0 };
+
+        vendor.push(if rng.chance(0.5) { 1i64 } else { 2 });
+        pickup.push(start.format("%Y-%m-%d %H:%M:%S").to_string());
+        dropoff.push((start + Duration::seconds((minutes * 60.0) as i64)).format("%Y-%m-%d %H:%M:%S").to_string());
+        passengers.push(count);
+        distance.push(dist);
+        pickup_lon.push(lon);
+        pickup_lat.push(lat);
+        dropoff_lon.push(lon2);
+        dropoff_lat.push(lat2);
+        payment.push(if card { 1i64 } else { 2 });
+        fare.push(meter);
+        extra.push(surcharge);
+        tip.push(tip_amount);
+        tolls.push(toll);
+        total.push(cents(meter + surcharge + 0.5 + tip_amount + toll + 0.3));
+    }
+
+    df!(
+        "VendorID" => vendor,
+        "tpep_pickup_datetime" => pickup,
+        "tpep_dropoff_datetime" => dropoff,
+        "passenger_count" => passengers,
+        "trip_distance" => distance,
+        "pickup_longitude" => pickup_lon,
+        "pickup_latitude" => pickup_lat,
+        "RateCodeID" => vec![1i64; rows],
+        "store_and_fwd_flag" => vec!["N"; rows],
+        "dropoff_longitude" => dropoff_lon,
+        "dropoff_latitude" => dropoff_lat,
+        "payment_type" => payment,
+        "fare_amount" => fare,
+        "extra" => extra,
+        "mta_tax" => vec![0.5; rows],
+        "tip_amount" => tip,
+        "tolls_amount" => tolls,
+        "improvement_surcharge" => vec![0.3; rows],
+        "total_amount" => total,
+    )
+}
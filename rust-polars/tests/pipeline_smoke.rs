@@ -0,0 +1,61 @@
+//! End-to-end smoke test: seeded synthetic trips through load → clean → aggregate.
+
+use polars::prelude::*;
+use polars_etl_benchmark::etl::{read_frame, write_frame, OutputFormat, PolarsETL};
+use polars_etl_benchmark::synthetic::generate_synthetic;
+use std::path::PathBuf;
+
+const ROWS: usize = 5_000;
+
+// A scratch directory removed when the test ends, pass or fail
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("polars-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create scratch dir");
+        Self(dir)
+    }
+
+    fn path(&self, file: &str) -> String {
+        self.0.join(file).display().to_string()
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn load_clean_aggregate_on_synthetic_trips() {
+    let scratch = ScratchDir::new("smoke");
+    // The file name declares January 2015, which the synthetic pickups fall in
+    let input = scratch.path("yellow_tripdata_2015-01.csv");
+    let mut trips = generate_synthetic(ROWS, 42).unwrap();
+    write_frame(&mut trips, &input, OutputFormat::Csv).unwrap();
+
+    let mut etl = PolarsETL::new();
+    etl.load_data(&input).unwrap().clean_data().unwrap().aggregate_data().unwrap();
+    let output = scratch.path("results");
+    etl.save_results(&output).unwrap();
+
+    let metrics = etl.get_metrics();
+    for stage in ["load", "clean", "aggregate"] {
+        assert!(metrics.contains_key(&format!("{}_time", stage)), "no {}_time", stage);
+    }
+    assert_eq!(metrics["out_of_period_rows"], 0.0);
+    assert!(etl.get_tags().contains_key("declared_period"));
+
+    let read = |stem: &str| read_frame(&format!("{}/{}.csv", output, stem), OutputFormat::Csv).unwrap();
+    let daily = read("polars_daily_stats");
+    assert_eq!(daily.height(), 31);
+    assert_eq!(read("polars_hourly_stats").height(), 24);
+    assert_eq!(read("polars_dow_stats").height(), 7);
+
+    // Cleaning drops the ~1% dirty rows and nothing else
+    let kept = daily.column("trip_count").unwrap().cast(&DataType::Int64).unwrap().i64().unwrap().sum().unwrap();
+    assert!(kept < ROWS as i64, "dirty rows survived cleaning");
+    assert!(kept > (ROWS as f64 * 0.95) as i64, "cleaning dropped {} of {} rows", ROWS as i64 - kept, ROWS);
+}